@@ -0,0 +1,158 @@
+use colored::*;
+use globset::{Glob, GlobMatcher};
+use serde::Deserialize;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::Child;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::shell::{spawn_unattended, stop};
+
+/// How often a running action is checked for completion or cancellation.
+const POLL: Duration = Duration::from_millis(100);
+
+/// A `[[watcher.actions]]` rule: when a changed file matches the `on` glob
+/// (relative to the watched folder), run the `run` shell command there.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct WatchAction {
+    pub on: String,
+    pub run: String,
+}
+
+struct Rule {
+    action: WatchAction,
+    matcher: GlobMatcher,
+    /// The in-flight run, if any.
+    running: Arc<Mutex<Option<Child>>>,
+    /// Bumped on every start so a superseded run knows it was cancelled.
+    generation: Arc<AtomicU64>,
+}
+
+/// Runs the configured actions for batches of file changes. A new batch
+/// that matches a rule cancels that rule's previous run if it's still going.
+pub struct ActionRunner {
+    rules: Vec<Rule>,
+    dir: PathBuf,
+    confirm_commands: Vec<String>,
+}
+
+impl ActionRunner {
+    pub fn new(actions: &[WatchAction], dir: &Path, confirm_commands: &[String]) -> Result<Self, String> {
+        let rules = actions
+            .iter()
+            .map(|action| {
+                let glob = Glob::new(&action.on)
+                    .map_err(|e| format!("invalid watcher action glob '{}': {}", action.on, e))?;
+                Ok(Rule {
+                    action: action.clone(),
+                    matcher: glob.compile_matcher(),
+                    running: Arc::new(Mutex::new(None)),
+                    generation: Arc::new(AtomicU64::new(0)),
+                })
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(Self {
+            rules,
+            dir: dir.to_path_buf(),
+            confirm_commands: confirm_commands.to_vec(),
+        })
+    }
+
+    /// Start every rule matching one of `paths` (relative to the watched folder).
+    pub fn trigger(&self, paths: &[&PathBuf]) {
+        for rule in &self.rules {
+            if paths.iter().any(|p| rule.matcher.is_match(p)) {
+                self.start(rule);
+            }
+        }
+    }
+
+    fn start(&self, rule: &Rule) {
+        let mut running = rule.running.lock().unwrap();
+        let generation = rule.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        if let Some(mut child) = running.take() {
+            stop(&mut child);
+        }
+
+        let mut child = match spawn_unattended(&rule.action.run, &self.dir, &self.confirm_commands) {
+            Ok(child) => child,
+            Err(err) => {
+                println!("{}", format!("⚠️  Watcher action skipped: {}", err).bright_yellow());
+                return;
+            }
+        };
+        let stdout = child.stdout.take().map(read_all);
+        let stderr = child.stderr.take().map(read_all);
+        *running = Some(child);
+
+        let slot = Arc::clone(&rule.running);
+        let current = Arc::clone(&rule.generation);
+        let command = rule.action.run.clone();
+        let started = Instant::now();
+        thread::spawn(move || loop {
+            thread::sleep(POLL);
+            let mut running = slot.lock().unwrap();
+            if current.load(Ordering::SeqCst) != generation {
+                return;
+            }
+            let status = match running.as_mut().map(Child::try_wait) {
+                Some(Ok(Some(status))) => status,
+                Some(Ok(None)) => continue,
+                Some(Err(err)) => {
+                    println!("{}", format!("❌ {} failed: {}", command, err).bright_red());
+                    running.take();
+                    return;
+                }
+                None => return,
+            };
+            running.take();
+            drop(running);
+
+            let output = [stdout, stderr]
+                .into_iter()
+                .flatten()
+                .filter_map(|reader| reader.join().ok())
+                .collect::<Vec<_>>()
+                .join("\n");
+            report(&command, status.code(), status.success(), started.elapsed(), &output);
+            return;
+        });
+    }
+}
+
+fn read_all(mut pipe: impl Read + Send + 'static) -> JoinHandle<String> {
+    thread::spawn(move || {
+        let mut bytes = Vec::new();
+        let _ = pipe.read_to_end(&mut bytes);
+        String::from_utf8_lossy(&bytes).into_owned()
+    })
+}
+
+/// One line per finished run; failures carry the most telling output line.
+fn report(command: &str, code: Option<i32>, success: bool, took: Duration, output: &str) {
+    let took = format!("{:.1}s", took.as_secs_f64());
+    if success {
+        println!("{} {} {}", "✅".green(), command.bright_white(), format!("passed ({})", took).bright_green());
+        return;
+    }
+
+    let lines: Vec<&str> = output.lines().map(str::trim).filter(|l| !l.is_empty()).collect();
+    let detail = lines
+        .iter()
+        .find(|l| l.starts_with("error"))
+        .or(lines.last())
+        .map(|l| format!(": {}", l))
+        .unwrap_or_default();
+    let code = code.map(|c| format!("exit {}, ", c)).unwrap_or_default();
+    println!(
+        "{} {} {}{}",
+        "❌".red(),
+        command.bright_white(),
+        format!("failed ({}{})", code, took).bright_red(),
+        detail.bright_black()
+    );
+}
@@ -0,0 +1,76 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Tracks when the user last did something (file edits seen by the watcher,
+/// commands typed into the REPL) and how long they have been at it.
+pub struct Activity {
+    idle_threshold: Duration,
+    state: Mutex<ActivityState>,
+}
+
+struct ActivityState {
+    last_seen: Instant,
+    streak_start: Instant,
+    /// Files the watcher saw change since the last break.
+    changed_files: BTreeSet<PathBuf>,
+}
+
+impl Activity {
+    pub fn new(idle_threshold: Duration) -> Self {
+        let now = Instant::now();
+        Self {
+            idle_threshold,
+            state: Mutex::new(ActivityState {
+                last_seen: now,
+                streak_start: now,
+                changed_files: BTreeSet::new(),
+            }),
+        }
+    }
+
+    pub fn idle_threshold(&self) -> Duration {
+        self.idle_threshold
+    }
+
+    /// Record activity. A gap longer than the idle threshold starts a new
+    /// streak of active coding.
+    pub fn touch(&self) {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+        if now - state.last_seen > self.idle_threshold {
+            state.streak_start = now;
+        }
+        state.last_seen = now;
+    }
+
+    /// Record activity caused by a change to the file at `path` (relative
+    /// to the watched folder).
+    pub fn file_changed(&self, path: &Path) {
+        self.touch();
+        self.state.lock().unwrap().changed_files.insert(path.to_path_buf());
+    }
+
+    /// Files changed since the last call, which is made at every break.
+    pub fn take_changed_files(&self) -> Vec<PathBuf> {
+        let mut state = self.state.lock().unwrap();
+        std::mem::take(&mut state.changed_files).into_iter().collect()
+    }
+
+    pub fn idle_for(&self) -> Duration {
+        self.state.lock().unwrap().last_seen.elapsed()
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.idle_for() > self.idle_threshold
+    }
+
+    /// Length of the current streak of continuous activity (zero when idle).
+    pub fn active_for(&self) -> Duration {
+        if self.is_idle() {
+            return Duration::ZERO;
+        }
+        self.state.lock().unwrap().streak_start.elapsed()
+    }
+}
@@ -0,0 +1,118 @@
+use chrono::{DateTime, Local};
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::watcher::{ChangeBatch, ChangeKind};
+
+/// How many changes are kept in memory (and loaded back from the log file).
+const MAX_RECORDS: usize = 10_000;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ChangeRecord {
+    pub at: String,
+    pub path: PathBuf,
+    pub kind: ChangeKind,
+}
+
+impl ChangeRecord {
+    fn local_time(&self) -> Option<DateTime<Local>> {
+        DateTime::parse_from_rfc3339(&self.at)
+            .ok()
+            .map(|t| t.with_timezone(&Local))
+    }
+}
+
+/// Every change the watcher reported, newest last. With a log file the
+/// records are also appended there as JSON lines, and the most recent ones
+/// are loaded back on startup.
+pub struct ChangeLog {
+    records: Mutex<VecDeque<ChangeRecord>>,
+    file: Option<PathBuf>,
+}
+
+impl ChangeLog {
+    pub fn new(file: Option<PathBuf>) -> Self {
+        let mut records = VecDeque::new();
+        if let Some(raw) = file.as_ref().and_then(|f| fs::read_to_string(f).ok()) {
+            records.extend(raw.lines().filter_map(|line| serde_json::from_str::<ChangeRecord>(line).ok()));
+            while records.len() > MAX_RECORDS {
+                records.pop_front();
+            }
+        }
+        Self {
+            records: Mutex::new(records),
+            file,
+        }
+    }
+
+    pub fn record(&self, path: &Path, kind: ChangeKind) {
+        let record = ChangeRecord {
+            at: Local::now().to_rfc3339(),
+            path: path.to_path_buf(),
+            kind,
+        };
+        if let Some(file) = &self.file {
+            let appended = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(file)
+                .and_then(|mut f| writeln!(f, "{}", serde_json::to_string(&record).unwrap_or_default()));
+            if let Err(err) = appended {
+                println!("{}", format!("⚠️  Can't write change log {}: {}", file.display(), err).bright_yellow());
+            }
+        }
+
+        let mut records = self.records.lock().unwrap();
+        records.push_back(record);
+        if records.len() > MAX_RECORDS {
+            records.pop_front();
+        }
+    }
+
+    /// Changes at or after `since`; everything with `None`.
+    pub fn since(&self, since: Option<DateTime<Local>>) -> Vec<ChangeRecord> {
+        self.records
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|r| since.is_none_or(|since| r.local_time().is_some_and(|at| at >= since)))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Print the net change per file (a file created and deleted again doesn't
+/// show up) with the time of its last change.
+pub fn print_changes(records: &[ChangeRecord], label: &str) {
+    let mut batch = ChangeBatch::default();
+    let mut last_seen = BTreeMap::new();
+    for record in records {
+        batch.add(record.path.clone(), record.kind);
+        last_seen.insert(record.path.clone(), record.local_time());
+    }
+
+    if batch.is_empty() {
+        println!("📜 No changes {}", label);
+        return;
+    }
+    println!("📜 Changes {}: {}", label, batch.summary().bright_white());
+    for (path, kind) in batch.iter() {
+        let at = last_seen
+            .get(path)
+            .copied()
+            .flatten()
+            .map(|t| t.format("%H:%M").to_string())
+            .unwrap_or_default();
+        println!(
+            "  {} {:<8} {}",
+            at.bright_black(),
+            kind.label(),
+            path.display().to_string().yellow()
+        );
+    }
+}
@@ -1,200 +1,268 @@
 use colored::*;
 use std::io::{self, Write};
-use std::path::Path;
-use std::process::Command;
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Duration;
-
-fn resolve_coffee_icon() -> Option<String> {
-    let image_paths = vec![
-        "./src/resource/coffee_icon.png",
-        "./src/resource/coffee_icon.jpg",
-        "./coffee_icon.png",
-        "./coffee_icon.icns",
-        "./coffee_icon.jpg",
-        "./coffee_icon.jpeg",
-        "./assets/coffee_icon.png",
-        "./assets/coffee_icon.icns",
-        "./assets/coffee_icon.jpg",
-        "./coffee.png",
-        "./coffee.jpg",
-    ];
-
-    for path in image_paths {
-        let candidate = Path::new(path);
-        if candidate.exists() {
-            if let Ok(abs) = std::fs::canonicalize(candidate) {
-                return Some(abs.to_string_lossy().to_string());
-            } else {
-                return Some(candidate.to_string_lossy().to_string());
-            }
-        }
-    }
+use std::time::{Duration, Instant};
 
-    None
-}
+use crate::activity::Activity;
+use crate::digest::{Digest, DigestSource};
+use crate::history::{EventKind, History};
+use crate::notifier::{Notification, NotificationKind, Notifiers, Urgency};
+use crate::pomodoro::{format_minutes, BreakPlan, Phase, TimerState, Transition};
 
-#[cfg(target_os = "macos")]
-fn run_osascript(script: &str, context: &str) {
-    match Command::new("osascript").arg("-e").arg(script).output() {
-        Ok(output) => {
-            if !output.status.success() {
-                eprintln!(
-                    "[coffee reminder] {} failed: {}",
-                    context,
-                    String::from_utf8_lossy(&output.stderr)
-                );
-            }
-        }
-        Err(err) => {
-            eprintln!("[coffee reminder] could not run osascript for {}: {}", context, err);
-        }
-    }
-}
+/// How often the scheduler thread wakes up to advance the timer.
+const TICK: Duration = Duration::from_secs(1);
 
-#[cfg(target_os = "windows")]
-fn run_powershell(script: &str, context: &str) {
-    match Command::new("powershell")
-        .arg("-NoLogo")
-        .arg("-NoProfile")
-        .arg("-Command")
-        .arg(script)
-        .output()
-    {
-        Ok(output) => {
-            if !output.status.success() {
-                eprintln!(
-                    "[coffee reminder] {} failed: {}",
-                    context,
-                    String::from_utf8_lossy(&output.stderr)
-                );
-            }
-        }
-        Err(err) => {
-            eprintln!(
-                "[coffee reminder] could not run PowerShell for {}: {}",
-                context, err
-            );
-        }
-    }
+/// Requests the REPL can send to the running scheduler.
+#[derive(Clone, Copy, Debug)]
+pub enum TimerCommand {
+    Pause,
+    Resume,
+    Snooze(Duration),
+    Skip,
+    Reset,
 }
 
-fn show_system_notification(title: &str, message: &str) {
-    #[cfg(target_os = "macos")]
-    {
-        let icon_clause = resolve_coffee_icon()
-            .map(|p| format!("with icon POSIX file \"{}\"", p))
-            .unwrap_or_else(|| "with icon note".to_string());
+/// Handle to the running break scheduler.
+pub struct BreakTimer {
+    state: Arc<Mutex<TimerState>>,
+    commands: Sender<TimerCommand>,
+}
 
-        let script = format!(
-            "display notification \"{}\" with title \"{}\" sound name \"Glass\" {}",
-            message, title, icon_clause
-        );
+impl BreakTimer {
+    pub fn snapshot(&self) -> TimerState {
+        self.state.lock().unwrap().clone()
+    }
 
-        run_osascript(&script, "notification");
+    pub fn send(&self, command: TimerCommand) {
+        // The scheduler thread lives as long as the process, so this only
+        // fails during shutdown.
+        let _ = self.commands.send(command);
     }
+}
 
-    #[cfg(target_os = "windows")]
-    {
-        fn escape_ps(value: &str) -> String {
-            value.replace('"', "`\"")
-        }
+/// Start the break scheduler. With `activity`, work time only counts while the
+/// user is active, and an idle gap longer than its threshold counts as a break.
+/// With `digest`, break reminders come with a short checkpoint digest.
+pub fn start_coffee_dance(
+    plan: BreakPlan,
+    notifiers: Arc<Notifiers>,
+    activity: Option<Arc<Activity>>,
+    history: Arc<Mutex<History>>,
+    digest: Option<DigestSource>,
+) -> BreakTimer {
+    let state = Arc::new(Mutex::new(TimerState::new(plan)));
+    let (tx, rx) = channel();
 
-        let icon_snippet = resolve_coffee_icon().map(|path| {
-            let url = format!(
-                "file:///{}",
-                path.replace('\\', "/").replace(' ', "%20")
-            );
-            format!(
-                "$xml.GetElementsByTagName(\"image\")[0].Attributes.GetNamedItem(\"src\").Value = \"{}\";",
-                url
-            )
-        }).unwrap_or_default();
-
-        let script = format!(
-            r#"
-Add-Type -AssemblyName System.Runtime.WindowsRuntime | Out-Null
-[Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime] | Out-Null
-$template = [Windows.UI.Notifications.ToastTemplateType]::ToastImageAndText02
-$xml = [Windows.UI.Notifications.ToastNotificationManager]::GetTemplateContent($template)
-$xml.GetElementsByTagName("text")[0].AppendChild($xml.CreateTextNode("{title}")) | Out-Null
-$xml.GetElementsByTagName("text")[1].AppendChild($xml.CreateTextNode("{message}")) | Out-Null
-{icon}
-$toast = [Windows.UI.Notifications.ToastNotification]::new($xml)
-$notifier = [Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier("coffee-break")
-$notifier.Show($toast)
-"#,
-            title = escape_ps(title),
-            message = escape_ps(message),
-            icon = icon_snippet
-        );
+    let mut scheduler = Scheduler {
+        state: Arc::clone(&state),
+        notifiers,
+        activity,
+        history,
+        digest,
+        away_since: None,
+    };
+    thread::spawn(move || scheduler.run(rx));
 
-        run_powershell(&script, "notification");
-    }
+    BreakTimer { state, commands: tx }
+}
 
-    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
-    {
-        println!("☕ {} - {}", title, message);
-    }
+struct Scheduler {
+    state: Arc<Mutex<TimerState>>,
+    notifiers: Arc<Notifiers>,
+    activity: Option<Arc<Activity>>,
+    history: Arc<Mutex<History>>,
+    digest: Option<DigestSource>,
+    /// Set while a work block is on hold because the user is idle.
+    away_since: Option<Instant>,
 }
 
-#[cfg(target_os = "macos")]
-fn show_coffee_dialog() {
-    // Show a compact modal dialog box with coffee theme
-    // This requires user interaction (clicking OK)
+impl Scheduler {
+    fn run(&mut self, rx: Receiver<TimerCommand>) {
+        let mut last_tick = Instant::now();
+        loop {
+            let command = match rx.recv_timeout(TICK) {
+                Ok(command) => Some(command),
+                Err(RecvTimeoutError::Timeout) => None,
+                Err(RecvTimeoutError::Disconnected) => return,
+            };
+            let now = Instant::now();
+            self.tick(now - last_tick, command);
+            last_tick = now;
+        }
+    }
 
-    let icon_clause = resolve_coffee_icon()
-        .map(|p| format!("with icon POSIX file \"{}\"", p))
-        .unwrap_or_else(|| "with icon note".to_string());
+    fn tick(&mut self, dt: Duration, command: Option<TimerCommand>) {
+        let idle_for = self
+            .activity
+            .as_ref()
+            .filter(|a| a.is_idle())
+            .map(|a| a.idle_for());
+        let mut events = Vec::new();
 
-    let script = format!(
-        r#"
-        tell application "System Events"
-            activate
-        end tell
-        
-        display dialog "☕ COFFEE BREAK TIME! ☕
+        let (transition, worked, snapshot) = {
+            let mut state = self.state.lock().unwrap();
+            let mut worked = state.elapsed;
+            let mut transition = match idle_for {
+                Some(idle_for) if state.phase == Phase::Work => {
+                    if self.away_since.is_none() {
+                        self.away_since = Some(Instant::now() - idle_for);
+                        // Time up to the last sign of activity was real work.
+                        let focused = worked.saturating_sub(idle_for);
+                        if !focused.is_zero() {
+                            events.push((EventKind::FocusBlock, Some(focused)));
+                        }
+                        if let Some(digest) = &self.digest {
+                            digest.activity.take_changed_files();
+                        }
+                        state.natural_break();
+                    }
+                    None
+                }
+                _ => {
+                    if !state.paused {
+                        worked += dt;
+                    }
+                    state.advance(dt)
+                }
+            };
+            if transition.is_none() {
+                transition = match command {
+                    Some(TimerCommand::Pause) => {
+                        state.paused = true;
+                        None
+                    }
+                    Some(TimerCommand::Resume) => {
+                        state.paused = false;
+                        None
+                    }
+                    Some(TimerCommand::Snooze(delay)) => {
+                        state.snooze(delay);
+                        events.push((EventKind::BreakSnoozed, Some(delay)));
+                        None
+                    }
+                    Some(TimerCommand::Skip) => {
+                        if state.phase == Phase::Work {
+                            events.push((EventKind::BreakSkipped, None));
+                        }
+                        state.skip()
+                    }
+                    Some(TimerCommand::Reset) => {
+                        state.reset();
+                        None
+                    }
+                    None => None,
+                };
+            }
+            (transition, worked, state.clone())
+        };
 
-You've been coding for a while!
-Time to take a break and grab some coffee! 🍵
+        if idle_for.is_none() {
+            if let Some(since) = self.away_since.take() {
+                let away = since.elapsed();
+                events.push((EventKind::BreakTaken, Some(away)));
+                println!(
+                    "\n{}",
+                    format!(
+                        "🌿 Welcome back! You were away for {}, so that counts as a break.",
+                        format_minutes(away)
+                    )
+                    .bright_green()
+                );
+            }
+        }
 
-Your code will still be here when you return!" with title "☕ Coffee Break" buttons {{"☕ OK ☕"}} default button 1 {} giving up after 30
-        "#,
-        icon_clause
-    );
-    
-    run_osascript(&script, "dialog");
-}
+        match transition {
+            Some(Transition::Reminder) | Some(Transition::BreakStarted(_)) => {
+                events.push((EventKind::FocusBlock, Some(worked)));
+                events.push((EventKind::Reminder, None));
+            }
+            Some(Transition::BreakOver) => events.push((EventKind::BreakTaken, Some(worked))),
+            None => {}
+        }
+        if !events.is_empty() {
+            let mut history = self.history.lock().unwrap();
+            for (kind, duration) in events {
+                history.record(kind, duration);
+            }
+        }
 
-#[cfg(not(target_os = "macos"))]
-fn show_coffee_dialog() {
-    // Dialog not available on this platform; notification already shown
+        if let Some(transition) = transition {
+            let digest = match (transition, &self.digest) {
+                (Transition::Reminder | Transition::BreakStarted(_), Some(source)) => {
+                    Some(source.collect(worked, &self.history.lock().unwrap()))
+                }
+                _ => None,
+            };
+            announce_transition(transition, worked, &snapshot, &self.notifiers, digest.as_ref());
+        }
+    }
 }
 
-pub fn start_coffee_dance(interval_minutes: u64) {
-    thread::spawn(move || {
-        loop {
-            // Wait for the specified interval (in minutes)
-            thread::sleep(Duration::from_secs(interval_minutes * 60));
-            
+fn announce_transition(
+    transition: Transition,
+    worked: Duration,
+    state: &TimerState,
+    notifiers: &Arc<Notifiers>,
+    digest: Option<&Digest>,
+) {
+    let with_digest = |message: String| match digest {
+        Some(digest) => format!("{}\n{}", message, digest.summary()),
+        None => message,
+    };
+    match transition {
+        Transition::Reminder => {
             // Show system notification popup
-            let message = if interval_minutes == 1 {
-                format!("You've been coding for {} minute! Time for coffee!", interval_minutes)
-            } else {
-                format!("You've been coding for {} minutes! Time for coffee!", interval_minutes)
-            };
-            show_system_notification("☕ Coffee Break Time!", &message);
-            
-            // Show modal dialog popup in IDE/terminal
-            show_coffee_dialog();
-            
+            let message = format!(
+                "You've been coding for {}! Time for coffee!",
+                format_minutes(worked)
+            );
+            notifiers.notify(&Notification::new(
+                NotificationKind::BreakReminder,
+                "☕ Coffee Break Time!",
+                &with_digest(message),
+            ));
+            if let Some(digest) = digest {
+                digest.print();
+            }
+
             // Start coffee dance animation directly
-            show_coffee_dance();
+            show_coffee_dance(Arc::clone(notifiers));
         }
-    });
+        Transition::BreakStarted(phase) => {
+            let message = format!(
+                "Focus block {} done! Enjoy a {} of {}.",
+                state.cycle,
+                phase.label().to_lowercase(),
+                format_minutes(state.phase_length())
+            );
+            notifiers.notify(&Notification::new(
+                NotificationKind::BreakReminder,
+                &format!("☕ {} Time!", phase.label()),
+                &with_digest(message),
+            ));
+            if let Some(digest) = digest {
+                digest.print();
+            }
+            show_coffee_dance(Arc::clone(notifiers));
+        }
+        Transition::BreakOver => {
+            let message = format!(
+                "Break over! Focus block {} starts now ({}).",
+                state.cycle + 1,
+                format_minutes(state.phase_length())
+            );
+            notifiers.notify(&Notification::new(
+                NotificationKind::BreakOver,
+                "💻 Back to Work!",
+                &message,
+            ));
+        }
+    }
 }
 
-pub fn show_coffee_dance() {
+pub fn show_coffee_dance(notifiers: Arc<Notifiers>) {
     // Run animation in a separate thread so it doesn't block REPL
     thread::spawn(move || {
         // Coffee dance animation frames
@@ -216,7 +284,10 @@ pub fn show_coffee_dance() {
         let mut frame_index = 0;
 
         // Show system notification
-        show_system_notification("☕ Coffee Dance!", "Coffee animation starting in terminal!");
+        notifiers.notify(
+            &Notification::new(NotificationKind::Info, "☕ Coffee Dance!", "Coffee animation starting in terminal!")
+                .with_urgency(Urgency::Low),
+        );
         
         // Print initial message
         println!("\n{}", "☕ Coffee dance starting! ☕".bright_yellow().bold());
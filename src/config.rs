@@ -0,0 +1,442 @@
+use clap::ValueEnum;
+use colored::*;
+use dirs::home_dir;
+use serde::Deserialize;
+use std::fmt::{self, Display};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use std::{env, fs};
+
+use crate::actions::WatchAction;
+use crate::memory::Memory;
+use crate::notifier::SinkKind;
+use crate::pomodoro::BreakPlan;
+
+/// Where an effective setting came from, lowest precedence first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Source {
+    Default,
+    UserFile,
+    ProjectFile,
+    Env,
+    Cli,
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Source::Default => "default",
+            Source::UserFile => "user config",
+            Source::ProjectFile => "project config",
+            Source::Env => "environment",
+            Source::Cli => "command line",
+        };
+        f.write_str(label)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Setting<T> {
+    pub value: T,
+    pub source: Source,
+}
+
+impl<T> Setting<T> {
+    fn new(value: T) -> Self {
+        Self {
+            value,
+            source: Source::Default,
+        }
+    }
+
+    fn apply(&mut self, value: Option<T>, source: Source) {
+        if let Some(value) = value {
+            self.value = value;
+            self.source = source;
+        }
+    }
+}
+
+/// One layer of settings. Every field is optional so layers only override
+/// what they mention. This is also the shape of the TOML files.
+#[derive(Deserialize, Default, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigLayer {
+    pub interval: Option<u64>,
+    pub idle_threshold: Option<u64>,
+    pub pomodoro: PomodoroLayer,
+    pub notify: NotifyLayer,
+    pub memory: MemoryLayer,
+    pub watcher: WatcherLayer,
+    pub insight: InsightLayer,
+    pub shell: ShellLayer,
+    pub digest: DigestLayer,
+}
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct PomodoroLayer {
+    pub enabled: Option<bool>,
+    pub work: Option<u64>,
+    pub short_break: Option<u64>,
+    pub long_break: Option<u64>,
+    pub long_break_every: Option<u32>,
+}
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct NotifyLayer {
+    pub sinks: Option<Vec<SinkKind>>,
+    pub command: Option<String>,
+    pub webhook_url: Option<String>,
+    pub icon_paths: Option<Vec<String>>,
+}
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct MemoryLayer {
+    pub file: Option<String>,
+}
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct WatcherLayer {
+    pub poll_interval_ms: Option<u64>,
+    pub debounce_ms: Option<u64>,
+    pub ignore: Option<Vec<String>>,
+    pub verbose: Option<bool>,
+    pub log_file: Option<String>,
+    pub actions: Option<Vec<WatchAction>>,
+}
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct InsightLayer {
+    pub ignore: Option<Vec<String>>,
+}
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct ShellLayer {
+    pub confirm_commands: Option<Vec<String>>,
+}
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct DigestLayer {
+    pub enabled: Option<bool>,
+    pub max_items: Option<usize>,
+}
+
+/// The effective settings after merging defaults, config files, environment
+/// variables and command-line flags (in that order; later wins).
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub interval: Setting<u64>,
+    pub idle_threshold: Setting<u64>,
+    pub pomodoro: Setting<bool>,
+    pub work: Setting<u64>,
+    pub short_break: Setting<u64>,
+    pub long_break: Setting<u64>,
+    pub long_break_every: Setting<u32>,
+    pub notify: Setting<Vec<SinkKind>>,
+    pub notify_command: Setting<Option<String>>,
+    pub webhook_url: Setting<Option<String>>,
+    pub icon_paths: Setting<Vec<String>>,
+    pub memory_file: Setting<PathBuf>,
+    pub watch_poll_ms: Setting<u64>,
+    pub watch_debounce_ms: Setting<u64>,
+    pub watch_ignore: Setting<Vec<String>>,
+    pub watch_verbose: Setting<bool>,
+    pub watch_log_file: Setting<Option<PathBuf>>,
+    pub watch_actions: Setting<Vec<WatchAction>>,
+    pub insight_ignore: Setting<Vec<String>>,
+    pub confirm_commands: Setting<Vec<String>>,
+    pub digest: Setting<bool>,
+    pub digest_max_items: Setting<usize>,
+    /// Config files that were found and merged.
+    pub files: Vec<PathBuf>,
+}
+
+fn strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|v| v.to_string()).collect()
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            interval: Setting::new(60),
+            idle_threshold: Setting::new(5),
+            pomodoro: Setting::new(false),
+            work: Setting::new(25),
+            short_break: Setting::new(5),
+            long_break: Setting::new(15),
+            long_break_every: Setting::new(4),
+            notify: Setting::new(vec![SinkKind::Desktop]),
+            notify_command: Setting::new(None),
+            webhook_url: Setting::new(None),
+            icon_paths: Setting::new(strings(&[
+                "./src/resource/coffee_icon.png",
+                "./src/resource/coffee_icon.jpg",
+                "./coffee_icon.png",
+                "./coffee_icon.icns",
+                "./coffee_icon.jpg",
+                "./coffee_icon.jpeg",
+                "./assets/coffee_icon.png",
+                "./assets/coffee_icon.icns",
+                "./assets/coffee_icon.jpg",
+                "./coffee.png",
+                "./coffee.jpg",
+            ])),
+            memory_file: Setting::new(Memory::default_path()),
+            watch_poll_ms: Setting::new(200),
+            watch_debounce_ms: Setting::new(500),
+            watch_ignore: Setting::new(strings(&[
+                "target/**", "node_modules/**", "*.swp", "*.swo", "*.swx", "*~", ".#*", "4913",
+            ])),
+            watch_verbose: Setting::new(false),
+            watch_log_file: Setting::new(None),
+            watch_actions: Setting::new(vec![]),
+            insight_ignore: Setting::new(strings(&["target", "node_modules", "__pycache__"])),
+            confirm_commands: Setting::new(strings(&["rm"])),
+            digest: Setting::new(false),
+            digest_max_items: Setting::new(5),
+            files: vec![],
+        }
+    }
+}
+
+impl Config {
+    /// `~/.config/coffee-break/config.toml`, or `$COFFEE_BREAK_CONFIG`.
+    pub fn user_path() -> Option<PathBuf> {
+        if let Ok(path) = env::var("COFFEE_BREAK_CONFIG") {
+            return Some(PathBuf::from(path));
+        }
+        home_dir().map(|home| home.join(".config").join("coffee-break").join("config.toml"))
+    }
+
+    pub fn project_path() -> PathBuf {
+        PathBuf::from(".coffee-break.toml")
+    }
+
+    /// Merge every layer on top of the defaults, with `cli` applied last.
+    pub fn load(cli: ConfigLayer) -> Result<Self, String> {
+        let mut config = Self::default();
+
+        if let Some(path) = Self::user_path() {
+            config.merge_file(&path, Source::UserFile)?;
+        }
+        config.merge_file(&Self::project_path(), Source::ProjectFile)?;
+        config.merge(env_layer()?, Source::Env);
+        config.merge(cli, Source::Cli);
+
+        Ok(config)
+    }
+
+    fn merge_file(&mut self, path: &Path, source: Source) -> Result<(), String> {
+        if !path.exists() {
+            return Ok(());
+        }
+        let raw = fs::read_to_string(path)
+            .map_err(|e| format!("could not read {}: {}", path.display(), e))?;
+        let layer: ConfigLayer =
+            toml::from_str(&raw).map_err(|e| format!("invalid config {}: {}", path.display(), e))?;
+        self.merge(layer, source);
+        self.files.push(path.to_path_buf());
+        Ok(())
+    }
+
+    fn merge(&mut self, layer: ConfigLayer, source: Source) {
+        self.interval.apply(layer.interval, source);
+        self.idle_threshold.apply(layer.idle_threshold, source);
+        self.pomodoro.apply(layer.pomodoro.enabled, source);
+        self.work.apply(layer.pomodoro.work, source);
+        self.short_break.apply(layer.pomodoro.short_break, source);
+        self.long_break.apply(layer.pomodoro.long_break, source);
+        self.long_break_every.apply(layer.pomodoro.long_break_every, source);
+        self.notify.apply(layer.notify.sinks, source);
+        self.notify_command.apply(layer.notify.command.map(Some), source);
+        self.webhook_url.apply(layer.notify.webhook_url.map(Some), source);
+        self.icon_paths.apply(layer.notify.icon_paths, source);
+        self.memory_file
+            .apply(layer.memory.file.as_deref().map(expand_home), source);
+        self.watch_poll_ms.apply(layer.watcher.poll_interval_ms, source);
+        self.watch_debounce_ms.apply(layer.watcher.debounce_ms, source);
+        self.watch_ignore.apply(layer.watcher.ignore, source);
+        self.watch_verbose.apply(layer.watcher.verbose, source);
+        self.watch_log_file
+            .apply(layer.watcher.log_file.as_deref().map(|f| Some(expand_home(f))), source);
+        self.watch_actions.apply(layer.watcher.actions, source);
+        self.insight_ignore.apply(layer.insight.ignore, source);
+        self.confirm_commands.apply(layer.shell.confirm_commands, source);
+        self.digest.apply(layer.digest.enabled, source);
+        self.digest_max_items.apply(layer.digest.max_items, source);
+    }
+
+    pub fn break_plan(&self) -> BreakPlan {
+        if self.pomodoro.value {
+            BreakPlan::Pomodoro {
+                work: Duration::from_secs(self.work.value * 60),
+                short_break: Duration::from_secs(self.short_break.value * 60),
+                long_break: Duration::from_secs(self.long_break.value * 60),
+                long_break_every: self.long_break_every.value.max(1),
+            }
+        } else {
+            BreakPlan::Simple {
+                interval: Duration::from_secs(self.interval.value * 60),
+            }
+        }
+    }
+
+    /// Every setting as `(key, value, source)` for display.
+    pub fn entries(&self) -> Vec<(&'static str, String, Source)> {
+        fn entry<T>(key: &'static str, s: &Setting<T>, show: impl Fn(&T) -> String) -> (&'static str, String, Source) {
+            (key, show(&s.value), s.source)
+        }
+        let list = |v: &Vec<String>| v.join(", ");
+        let optional = |v: &Option<String>| v.clone().unwrap_or_else(|| "-".to_string());
+
+        vec![
+            entry("interval", &self.interval, |v| format!("{} min", v)),
+            entry("idle_threshold", &self.idle_threshold, |v| format!("{} min", v)),
+            entry("pomodoro.enabled", &self.pomodoro, |v| v.to_string()),
+            entry("pomodoro.work", &self.work, |v| format!("{} min", v)),
+            entry("pomodoro.short_break", &self.short_break, |v| format!("{} min", v)),
+            entry("pomodoro.long_break", &self.long_break, |v| format!("{} min", v)),
+            entry("pomodoro.long_break_every", &self.long_break_every, |v| v.to_string()),
+            entry("notify.sinks", &self.notify, |v| {
+                v.iter()
+                    .filter_map(|k| k.to_possible_value())
+                    .map(|p| p.get_name().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            }),
+            entry("notify.command", &self.notify_command, optional),
+            entry("notify.webhook_url", &self.webhook_url, optional),
+            entry("notify.icon_paths", &self.icon_paths, list),
+            entry("memory.file", &self.memory_file, |v| v.display().to_string()),
+            entry("watcher.poll_interval_ms", &self.watch_poll_ms, |v| v.to_string()),
+            entry("watcher.debounce_ms", &self.watch_debounce_ms, |v| v.to_string()),
+            entry("watcher.ignore", &self.watch_ignore, list),
+            entry("watcher.verbose", &self.watch_verbose, |v| v.to_string()),
+            entry("watcher.log_file", &self.watch_log_file, |v| {
+                v.as_ref().map(|p| p.display().to_string()).unwrap_or_else(|| "-".to_string())
+            }),
+            entry("watcher.actions", &self.watch_actions, |v| {
+                let rules: Vec<String> = v.iter().map(|a| format!("{} → {}", a.on, a.run)).collect();
+                if rules.is_empty() { "-".to_string() } else { rules.join("; ") }
+            }),
+            entry("insight.ignore", &self.insight_ignore, list),
+            entry("shell.confirm_commands", &self.confirm_commands, list),
+            entry("digest.enabled", &self.digest, |v| v.to_string()),
+            entry("digest.max_items", &self.digest_max_items, |v| v.to_string()),
+        ]
+    }
+}
+
+/// Expand a leading `~/` to the home directory.
+pub fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+fn env_value<T: std::str::FromStr>(name: &str) -> Result<Option<T>, String> {
+    match env::var(name) {
+        Ok(raw) if !raw.trim().is_empty() => raw
+            .trim()
+            .parse()
+            .map(Some)
+            .map_err(|_| format!("invalid value for {}: '{}'", name, raw)),
+        _ => Ok(None),
+    }
+}
+
+fn env_list(name: &str) -> Option<Vec<String>> {
+    env::var(name).ok().map(|raw| {
+        raw.split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect()
+    })
+}
+
+/// Settings from `COFFEE_BREAK_*` environment variables.
+fn env_layer() -> Result<ConfigLayer, String> {
+    let sinks = match env_list("COFFEE_BREAK_NOTIFY") {
+        Some(names) => Some(
+            names
+                .iter()
+                .map(|n| SinkKind::from_str(n, true).map_err(|_| format!("unknown sink in COFFEE_BREAK_NOTIFY: '{}'", n)))
+                .collect::<Result<Vec<_>, _>>()?,
+        ),
+        None => None,
+    };
+
+    Ok(ConfigLayer {
+        interval: env_value("COFFEE_BREAK_INTERVAL")?,
+        idle_threshold: env_value("COFFEE_BREAK_IDLE_THRESHOLD")?,
+        pomodoro: PomodoroLayer {
+            enabled: env_value("COFFEE_BREAK_POMODORO")?,
+            work: env_value("COFFEE_BREAK_WORK")?,
+            short_break: env_value("COFFEE_BREAK_SHORT_BREAK")?,
+            long_break: env_value("COFFEE_BREAK_LONG_BREAK")?,
+            long_break_every: env_value("COFFEE_BREAK_LONG_BREAK_EVERY")?,
+        },
+        notify: NotifyLayer {
+            sinks,
+            command: env_value("COFFEE_BREAK_NOTIFY_COMMAND")?,
+            webhook_url: env_value("COFFEE_BREAK_WEBHOOK_URL")?,
+            icon_paths: env_list("COFFEE_BREAK_ICON_PATHS"),
+        },
+        memory: MemoryLayer {
+            file: env_value("COFFEE_BREAK_MEMORY_FILE")?,
+        },
+        watcher: WatcherLayer {
+            poll_interval_ms: env_value("COFFEE_BREAK_WATCH_POLL_MS")?,
+            debounce_ms: env_value("COFFEE_BREAK_WATCH_DEBOUNCE_MS")?,
+            ignore: env_list("COFFEE_BREAK_WATCH_IGNORE"),
+            verbose: env_value("COFFEE_BREAK_WATCH_VERBOSE")?,
+            log_file: env_value("COFFEE_BREAK_WATCH_LOG_FILE")?,
+            actions: None,
+        },
+        insight: InsightLayer {
+            ignore: env_list("COFFEE_BREAK_INSIGHT_IGNORE"),
+        },
+        shell: ShellLayer {
+            confirm_commands: env_list("COFFEE_BREAK_CONFIRM_COMMANDS"),
+        },
+        digest: DigestLayer {
+            enabled: env_value("COFFEE_BREAK_DIGEST")?,
+            max_items: env_value("COFFEE_BREAK_DIGEST_MAX_ITEMS")?,
+        },
+    })
+}
+
+pub fn print_config(config: &Config) {
+    println!("\n{}", "⚙️  Effective configuration:".bright_yellow().bold());
+    for (key, value, source) in config.entries() {
+        let source = match source {
+            Source::Default => source.to_string().bright_black(),
+            _ => source.to_string().bright_green(),
+        };
+        println!("  {:<28} {}  ({})", key.bright_cyan(), value.bright_white(), source);
+    }
+
+    println!("\n{}", "📄 Config files:".bright_yellow());
+    if let Some(path) = Config::user_path() {
+        print_file_status(config, &path, "user");
+    }
+    print_file_status(config, &Config::project_path(), "project");
+    println!();
+}
+
+fn print_file_status(config: &Config, path: &Path, label: &str) {
+    let status = if config.files.iter().any(|f| f == path) {
+        "loaded".bright_green()
+    } else {
+        "not found".bright_black()
+    };
+    println!("  {} {} ({})", format!("{}:", label).bright_white(), path.display(), status);
+}
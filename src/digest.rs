@@ -0,0 +1,94 @@
+use colored::*;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::activity::Activity;
+use crate::history::History;
+use crate::memory::Memory;
+use crate::pomodoro::{format_clock, format_minutes};
+
+/// Where the break digest gets its data from.
+pub struct DigestSource {
+    pub memory: Arc<Mutex<Memory>>,
+    pub project: String,
+    pub activity: Arc<Activity>,
+    /// How many notes and files to list before summarising the rest.
+    pub max_items: usize,
+}
+
+/// A checkpoint shown with a break reminder: what's still open, what was
+/// touched and how long the user has been focused.
+pub struct Digest {
+    pub focus: Duration,
+    pub focus_today: Duration,
+    pub changed_files: Vec<PathBuf>,
+    /// `(id, text)` of the project's open notes, pinned first.
+    pub open_notes: Vec<(u64, String)>,
+    max_items: usize,
+}
+
+impl DigestSource {
+    /// Gather the digest for a work block of length `focus`. This also
+    /// starts a fresh list of changed files for the next block.
+    pub fn collect(&self, focus: Duration, history: &History) -> Digest {
+        let open_notes = self
+            .memory
+            .lock()
+            .unwrap()
+            .sorted(Some(&self.project))
+            .into_iter()
+            .filter(|e| !e.done)
+            .map(|e| (e.id, e.text.clone()))
+            .collect();
+
+        Digest {
+            focus,
+            focus_today: Duration::from_secs(history.stats().today.focus_secs),
+            changed_files: self.activity.take_changed_files(),
+            open_notes,
+            max_items: self.max_items,
+        }
+    }
+}
+
+impl Digest {
+    /// One line for the notification body.
+    pub fn summary(&self) -> String {
+        format!(
+            "{} open note(s) · {} file(s) changed · {} focus today",
+            self.open_notes.len(),
+            self.changed_files.len(),
+            format_clock(self.focus_today)
+        )
+    }
+
+    pub fn print(&self) {
+        println!("\n{}", "📋 Break digest".bright_cyan().bold());
+        println!(
+            "  {} {} this block, {} today",
+            "⏱  Focus:".bright_white(),
+            format_minutes(self.focus),
+            format_clock(self.focus_today)
+        );
+
+        println!("  {} {}", "📂 Files changed since last break:".bright_white(), self.changed_files.len());
+        for path in self.changed_files.iter().take(self.max_items) {
+            println!("     {}", path.display().to_string().yellow());
+        }
+        self.print_more(self.changed_files.len());
+
+        println!("  {} {}", "📝 Open notes:".bright_white(), self.open_notes.len());
+        for (id, text) in self.open_notes.iter().take(self.max_items) {
+            println!("     {} {}", format!("#{}", id).bright_cyan(), text.bright_yellow());
+        }
+        self.print_more(self.open_notes.len());
+        println!();
+    }
+
+    fn print_more(&self, total: usize) {
+        if total > self.max_items {
+            println!("     {}", format!("… and {} more", total - self.max_items).bright_black());
+        }
+    }
+}
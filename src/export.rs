@@ -0,0 +1,282 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::expand_home;
+use crate::memory::{parse_tags, MemoryEntry};
+
+/// Marks the hidden metadata comment on each exported Markdown line.
+const MD_MARKER: &str = "<!-- coffee-break ";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Markdown,
+    JsonLines,
+    Csv,
+}
+
+impl ExportFormat {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "md" | "markdown" => Some(Self::Markdown),
+            "jsonl" | "ndjson" => Some(Self::JsonLines),
+            "csv" => Some(Self::Csv),
+            _ => None,
+        }
+    }
+
+    /// Guess the format from a file extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        path.extension().and_then(|e| e.to_str()).and_then(Self::parse)
+    }
+}
+
+/// A parsed `mem export` / `mem import` command line.
+#[derive(Debug)]
+pub struct TransferArgs {
+    pub format: Option<ExportFormat>,
+    pub path: Option<PathBuf>,
+    pub all: bool,
+}
+
+/// Parse `[--format md|jsonl|csv] [--all] [path]`.
+pub fn parse_transfer_args(args: &str) -> Result<TransferArgs, String> {
+    let mut parsed = TransferArgs {
+        format: None,
+        path: None,
+        all: false,
+    };
+    let mut words = args.split_whitespace();
+    while let Some(word) = words.next() {
+        match word {
+            "--format" => {
+                let name = words.next().ok_or("--format needs a value")?;
+                parsed.format = Some(ExportFormat::parse(name).ok_or_else(|| {
+                    format!("unknown format '{}' (use md, jsonl or csv)", name)
+                })?);
+            }
+            "--all" => parsed.all = true,
+            flag if flag.starts_with("--") => return Err(format!("unknown option {}", flag)),
+            path if parsed.path.is_none() => parsed.path = Some(expand_home(path)),
+            extra => return Err(format!("unexpected argument '{}'", extra)),
+        }
+    }
+    Ok(parsed)
+}
+
+/// One row of the CSV format; tags are space separated and an empty
+/// project means a global note.
+#[derive(Serialize, Deserialize)]
+struct CsvRow {
+    id: u64,
+    text: String,
+    tags: String,
+    project: String,
+    done: bool,
+    pinned: bool,
+    created_at: String,
+    updated_at: String,
+    #[serde(default)]
+    due: String,
+    #[serde(default)]
+    reminded: bool,
+}
+
+/// Metadata hidden in an HTML comment so Markdown exports can be imported
+/// again without showing up when pasted somewhere that renders them.
+#[derive(Serialize, Deserialize)]
+struct MarkdownMeta {
+    id: u64,
+    created_at: String,
+    updated_at: String,
+    #[serde(default)]
+    project: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    due: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    reminded: bool,
+}
+
+pub fn export_entries(entries: &[&MemoryEntry], format: ExportFormat) -> Result<String, String> {
+    match format {
+        ExportFormat::Markdown => Ok(export_markdown(entries)),
+        ExportFormat::JsonLines => {
+            let mut out = String::new();
+            for entry in entries {
+                out.push_str(&serde_json::to_string(entry).map_err(|e| e.to_string())?);
+                out.push('\n');
+            }
+            Ok(out)
+        }
+        ExportFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(vec![]);
+            for entry in entries {
+                writer
+                    .serialize(CsvRow {
+                        id: entry.id,
+                        text: entry.text.clone(),
+                        tags: entry.tags.join(" "),
+                        project: entry.project.clone().unwrap_or_default(),
+                        done: entry.done,
+                        pinned: entry.pinned,
+                        created_at: entry.created_at.clone(),
+                        updated_at: entry.updated_at.clone(),
+                        due: entry.due.clone().unwrap_or_default(),
+                        reminded: entry.reminded,
+                    })
+                    .map_err(|e| e.to_string())?;
+            }
+            let bytes = writer.into_inner().map_err(|e| e.to_string())?;
+            String::from_utf8(bytes).map_err(|e| e.to_string())
+        }
+    }
+}
+
+/// A task list grouped by project, ready to paste into a standup.
+fn export_markdown(entries: &[&MemoryEntry]) -> String {
+    let mut groups: Vec<(Option<&str>, Vec<&MemoryEntry>)> = Vec::new();
+    for entry in entries {
+        let name = entry.project.as_ref().map(|_| entry.project_name().unwrap_or_default());
+        match groups.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, group)) => group.push(entry),
+            None => groups.push((name, vec![entry])),
+        }
+    }
+    // Project groups first, global notes last.
+    groups.sort_by_key(|(name, _)| (name.is_none(), *name));
+
+    let mut out = format!("# Coffee Break memories ({})\n", Utc::now().format("%Y-%m-%d"));
+    for (name, group) in groups {
+        out.push_str(&format!("\n## {}\n\n", name.unwrap_or("Global")));
+        for entry in group {
+            let check = if entry.done { "[x]" } else { "[ ]" };
+            let pin = if entry.pinned { "📌 " } else { "" };
+            let tags: String = entry.tags.iter().map(|t| format!(" #{}", t)).collect();
+            let meta = MarkdownMeta {
+                id: entry.id,
+                created_at: entry.created_at.clone(),
+                updated_at: entry.updated_at.clone(),
+                project: entry.project.clone(),
+                due: entry.due.clone(),
+                reminded: entry.reminded,
+            };
+            out.push_str(&format!(
+                "- {} {}{}{} {}{} -->\n",
+                check,
+                pin,
+                entry.text,
+                tags,
+                MD_MARKER,
+                serde_json::to_string(&meta).unwrap_or_default()
+            ));
+        }
+    }
+    out
+}
+
+/// Write an export to `path`, or to stdout without one.
+pub fn write_export(contents: &str, path: Option<&Path>) -> Result<(), String> {
+    match path {
+        Some(path) => fs::write(path, contents)
+            .map_err(|e| format!("Can't write {}: {}", path.display(), e)),
+        None => {
+            print!("{}", contents);
+            Ok(())
+        }
+    }
+}
+
+/// Read entries from an exported file. Entries without an id (e.g. Markdown
+/// lines typed by hand) get id 0 and are assigned a fresh one on import.
+pub fn read_import(path: &Path, format: ExportFormat) -> Result<Vec<MemoryEntry>, String> {
+    let raw = fs::read_to_string(path).map_err(|e| format!("Can't read {}: {}", path.display(), e))?;
+    let invalid = |line: usize, err: String| format!("{}:{}: {}", path.display(), line, err);
+
+    match format {
+        ExportFormat::JsonLines => raw
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| serde_json::from_str(line).map_err(|e| invalid(i + 1, e.to_string())))
+            .collect(),
+        ExportFormat::Csv => csv::Reader::from_reader(raw.as_bytes())
+            .deserialize::<CsvRow>()
+            .enumerate()
+            .map(|(i, row)| {
+                let row = row.map_err(|e| invalid(i + 2, e.to_string()))?;
+                Ok(MemoryEntry {
+                    id: row.id,
+                    text: row.text,
+                    created_at: row.created_at,
+                    updated_at: row.updated_at,
+                    tags: row.tags.split_whitespace().map(str::to_string).collect(),
+                    project: (!row.project.is_empty()).then_some(row.project),
+                    done: row.done,
+                    pinned: row.pinned,
+                    due: (!row.due.is_empty()).then_some(row.due),
+                    reminded: row.reminded,
+                })
+            })
+            .collect(),
+        ExportFormat::Markdown => raw
+            .lines()
+            .enumerate()
+            .filter_map(|(i, line)| parse_markdown_line(line).map(|r| r.map_err(|e| invalid(i + 1, e))))
+            .collect(),
+    }
+}
+
+/// Parse a `- [ ] text #tags <!-- coffee-break {...} -->` line; other lines
+/// (headings, blanks, prose) are skipped.
+fn parse_markdown_line(line: &str) -> Option<Result<MemoryEntry, String>> {
+    let line = line.trim();
+    let (done, rest) = if let Some(rest) = line.strip_prefix("- [ ] ") {
+        (false, rest)
+    } else if let Some(rest) = line.strip_prefix("- [x] ").or_else(|| line.strip_prefix("- [X] ")) {
+        (true, rest)
+    } else {
+        return None;
+    };
+
+    let (body, meta) = match rest.split_once(MD_MARKER) {
+        Some((body, meta)) => {
+            let json = meta.trim_end().trim_end_matches("-->").trim();
+            match serde_json::from_str::<MarkdownMeta>(json) {
+                Ok(meta) => (body, Some(meta)),
+                Err(e) => return Some(Err(e.to_string())),
+            }
+        }
+        None => (rest, None),
+    };
+    let (pinned, body) = match body.strip_prefix("📌 ") {
+        Some(body) => (true, body),
+        None => (false, body),
+    };
+    let (text, tags) = parse_tags(body);
+    if text.is_empty() {
+        return None;
+    }
+
+    let now = Utc::now().to_rfc3339();
+    let meta = meta.unwrap_or(MarkdownMeta {
+        id: 0,
+        created_at: now.clone(),
+        updated_at: now,
+        project: None,
+        due: None,
+        reminded: false,
+    });
+    Some(Ok(MemoryEntry {
+        id: meta.id,
+        text,
+        created_at: meta.created_at,
+        updated_at: meta.updated_at,
+        tags,
+        project: meta.project,
+        done,
+        pinned,
+        due: meta.due,
+        reminded: meta.reminded,
+    }))
+}
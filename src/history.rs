@@ -0,0 +1,240 @@
+use chrono::{DateTime, Duration as ChronoDuration, Local, NaiveDate, Utc};
+use colored::*;
+use dirs::home_dir;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::time::Duration;
+use std::{fs, path::PathBuf};
+
+use crate::pomodoro::format_clock;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    SessionStart,
+    SessionEnd,
+    /// A work block ended (reminder fired or the user wandered off).
+    FocusBlock,
+    Reminder,
+    BreakTaken,
+    BreakSnoozed,
+    BreakSkipped,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HistoryEvent {
+    pub at: String,
+    pub kind: EventKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_secs: Option<u64>,
+}
+
+impl HistoryEvent {
+    fn local_time(&self) -> Option<DateTime<Local>> {
+        DateTime::parse_from_rfc3339(&self.at)
+            .ok()
+            .map(|t| t.with_timezone(&Local))
+    }
+}
+
+/// Log of sessions and breaks, stored next to the memory file.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct History {
+    pub events: Vec<HistoryEvent>,
+    pub last_updated: String,
+}
+
+impl History {
+    pub fn path() -> PathBuf {
+        let mut p = home_dir().unwrap();
+        p.push(".coffee_break_history.json");
+        p
+    }
+
+    pub fn load() -> Self {
+        let path = Self::path();
+        if path.exists() {
+            let raw = fs::read_to_string(path).ok();
+            if let Some(json) = raw {
+                serde_json::from_str(&json).unwrap_or_else(|_| Self::empty())
+            } else {
+                Self::empty()
+            }
+        } else {
+            Self::empty()
+        }
+    }
+
+    pub fn empty() -> Self {
+        Self {
+            events: vec![],
+            last_updated: Utc::now().to_rfc3339(),
+        }
+    }
+
+    pub fn save(&mut self) {
+        self.last_updated = Utc::now().to_rfc3339();
+        let raw = serde_json::to_string_pretty(self).unwrap();
+        let _ = fs::write(Self::path(), raw);
+    }
+
+    /// Append an event and persist right away so a crash loses nothing.
+    pub fn record(&mut self, kind: EventKind, duration: Option<Duration>) {
+        self.events.push(HistoryEvent {
+            at: Utc::now().to_rfc3339(),
+            kind,
+            duration_secs: duration.map(|d| d.as_secs()),
+        });
+        self.save();
+    }
+
+    /// When the last break started or ended: the end of the latest focus
+    /// block or break.
+    pub fn last_break(&self) -> Option<DateTime<Local>> {
+        self.events
+            .iter()
+            .rev()
+            .filter(|e| matches!(e.kind, EventKind::FocusBlock | EventKind::BreakTaken))
+            .find_map(|e| e.local_time())
+    }
+
+    pub fn stats(&self) -> Stats {
+        let today = Local::now().date_naive();
+        let week_start = today - ChronoDuration::days(6);
+
+        let mut stats = Stats::default();
+        let mut break_days = BTreeSet::new();
+        let mut focus_total = 0u64;
+        let mut focus_blocks = 0u64;
+
+        for event in &self.events {
+            let Some(at) = event.local_time() else {
+                continue;
+            };
+            let day = at.date_naive();
+            let secs = event.duration_secs.unwrap_or(0);
+
+            if event.kind == EventKind::FocusBlock {
+                focus_total += secs;
+                focus_blocks += 1;
+            }
+            if event.kind == EventKind::BreakTaken {
+                break_days.insert(day);
+            }
+            if day == today {
+                stats.today.add(event.kind, secs);
+            }
+            if day >= week_start {
+                stats.week.add(event.kind, secs);
+            }
+        }
+
+        stats.average_focus = focus_total.checked_div(focus_blocks).map(Duration::from_secs);
+        stats.current_streak = current_streak(&break_days, today);
+        stats.best_streak = best_streak(&break_days);
+        stats
+    }
+}
+
+#[derive(Default, Debug, Clone, Copy)]
+pub struct Totals {
+    pub sessions: u64,
+    pub reminders: u64,
+    pub breaks_taken: u64,
+    pub breaks_snoozed: u64,
+    pub breaks_skipped: u64,
+    pub focus_secs: u64,
+    pub break_secs: u64,
+}
+
+impl Totals {
+    fn add(&mut self, kind: EventKind, secs: u64) {
+        match kind {
+            EventKind::SessionStart => self.sessions += 1,
+            EventKind::SessionEnd => {}
+            EventKind::FocusBlock => self.focus_secs += secs,
+            EventKind::Reminder => self.reminders += 1,
+            EventKind::BreakTaken => {
+                self.breaks_taken += 1;
+                self.break_secs += secs;
+            }
+            EventKind::BreakSnoozed => self.breaks_snoozed += 1,
+            EventKind::BreakSkipped => self.breaks_skipped += 1,
+        }
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct Stats {
+    pub today: Totals,
+    pub week: Totals,
+    /// Consecutive days (ending today or yesterday) with at least one break.
+    pub current_streak: u64,
+    pub best_streak: u64,
+    pub average_focus: Option<Duration>,
+}
+
+fn current_streak(days: &BTreeSet<NaiveDate>, today: NaiveDate) -> u64 {
+    let mut day = if days.contains(&today) {
+        today
+    } else {
+        today - ChronoDuration::days(1)
+    };
+    let mut streak = 0;
+    while days.contains(&day) {
+        streak += 1;
+        day -= ChronoDuration::days(1);
+    }
+    streak
+}
+
+fn best_streak(days: &BTreeSet<NaiveDate>) -> u64 {
+    let mut best = 0;
+    let mut run = 0;
+    let mut previous: Option<NaiveDate> = None;
+    for day in days {
+        run = match previous {
+            Some(p) if *day - p == ChronoDuration::days(1) => run + 1,
+            _ => 1,
+        };
+        best = best.max(run);
+        previous = Some(*day);
+    }
+    best
+}
+
+pub fn print_stats(stats: &Stats) {
+    println!("\n{}", "=".repeat(60).bright_cyan());
+    println!("{}", "📈 COFFEE BREAK STATS".bright_cyan().bold());
+    println!("{}\n", "=".repeat(60).bright_cyan());
+
+    for (label, totals) in [("📅 Today:", &stats.today), ("🗓️  Last 7 days:", &stats.week)] {
+        println!("{}", label.bright_yellow());
+        println!("  {} {}", "Sessions:".bright_white(), totals.sessions);
+        println!("  {} {}", "Focus time:".bright_white(), format_clock(Duration::from_secs(totals.focus_secs)));
+        println!("  {} {}", "Reminders:".bright_white(), totals.reminders);
+        println!(
+            "  {} {} ({} total)",
+            "Breaks taken:".bright_white(),
+            totals.breaks_taken.to_string().bright_green(),
+            format_clock(Duration::from_secs(totals.break_secs))
+        );
+        println!("  {} {}", "Snoozed:".bright_white(), totals.breaks_snoozed.to_string().bright_yellow());
+        println!("  {} {}", "Skipped:".bright_white(), totals.breaks_skipped.to_string().bright_red());
+        println!();
+    }
+
+    println!(
+        "{} {} day(s) (best: {})",
+        "🔥 Break streak:".bright_yellow(),
+        stats.current_streak.to_string().bright_green(),
+        stats.best_streak
+    );
+    match stats.average_focus {
+        Some(avg) => println!("{} {}", "⏱  Average focus block:".bright_yellow(), format_clock(avg)),
+        None => println!("{} {}", "⏱  Average focus block:".bright_yellow(), "no data yet".bright_white()),
+    }
+
+    println!("\n{}", "=".repeat(60).bright_cyan());
+    println!();
+}
@@ -12,25 +12,38 @@ pub struct ProjectInsight {
     pub structure: Vec<String>,
 }
 
-pub fn analyze_project(path: &str) -> ProjectInsight {
-    let mut file_counts: HashMap<String, usize> = HashMap::new();
-    let mut dependencies: Vec<String> = Vec::new();
-    let mut structure: Vec<String> = Vec::new();
-    let mut total_files = 0;
-
+pub fn analyze_project(path: &str, ignore: &[String]) -> ProjectInsight {
     let project_type = detect_project_type(path);
-    
-    scan_directory(Path::new(path), 0, &mut file_counts, &mut dependencies, &mut structure, &mut total_files, 3);
+
+    let mut scan = Scan {
+        ignore,
+        max_depth: 3,
+        file_counts: HashMap::new(),
+        dependencies: Vec::new(),
+        structure: Vec::new(),
+        total_files: 0,
+    };
+    scan_directory(Path::new(path), 0, &mut scan);
 
     ProjectInsight {
         project_type,
-        file_counts,
-        total_files,
-        dependencies,
-        structure,
+        file_counts: scan.file_counts,
+        total_files: scan.total_files,
+        dependencies: scan.dependencies,
+        structure: scan.structure,
     }
 }
 
+/// Settings and accumulated results of a directory walk.
+struct Scan<'a> {
+    ignore: &'a [String],
+    max_depth: usize,
+    file_counts: HashMap<String, usize>,
+    dependencies: Vec<String>,
+    structure: Vec<String>,
+    total_files: usize,
+}
+
 fn detect_project_type(path: &str) -> String {
     let path = Path::new(path);
     
@@ -62,16 +75,8 @@ fn detect_project_type(path: &str) -> String {
     "📁 Mixed/Unknown".to_string()
 }
 
-fn scan_directory(
-    dir: &Path,
-    depth: usize,
-    file_counts: &mut HashMap<String, usize>,
-    dependencies: &mut Vec<String>,
-    structure: &mut Vec<String>,
-    total_files: &mut usize,
-    max_depth: usize,
-) {
-    if depth > max_depth {
+fn scan_directory(dir: &Path, depth: usize, scan: &mut Scan) {
+    if depth > scan.max_depth {
         return;
     }
 
@@ -95,26 +100,18 @@ fn scan_directory(
             continue;
         }
 
-        if file_name_str == "target" || file_name_str == "node_modules" || file_name_str == "__pycache__" {
+        if scan.ignore.iter().any(|name| *name == file_name_str) {
             continue;
         }
 
         if path.is_dir() {
             let indent = "  ".repeat(depth);
             let dir_name = format!("{}/", file_name_str);
-            structure.push(format!("{}{}", indent, dir_name.bright_blue()));
+            scan.structure.push(format!("{}{}", indent, dir_name.bright_blue()));
             
-            scan_directory(
-                &path,
-                depth + 1,
-                file_counts,
-                dependencies,
-                structure,
-                total_files,
-                max_depth,
-            );
+            scan_directory(&path, depth + 1, scan);
         } else {
-            *total_files += 1;
+            scan.total_files += 1;
             
             // Get file extension
             let ext = path
@@ -123,13 +120,13 @@ fn scan_directory(
                 .unwrap_or("no-ext")
                 .to_string();
 
-            *file_counts.entry(ext.clone()).or_insert(0) += 1;
+            *scan.file_counts.entry(ext.clone()).or_insert(0) += 1;
 
             // Detect dependency files
             let file_name_str_ref: &str = &file_name_str;
             match file_name_str_ref {
                 "Cargo.toml" | "package.json" | "requirements.txt" | "go.mod" | "pom.xml" | "Gemfile" | "composer.json" => {
-                    dependencies.push(file_name_str.to_string());
+                    scan.dependencies.push(file_name_str.to_string());
                 }
                 _ => {}
             }
@@ -137,7 +134,7 @@ fn scan_directory(
             if depth < 2 {
                 let indent = "  ".repeat(depth);
                 let file_name_colored = file_name_str.to_string().bright_white();
-                structure.push(format!("{}{}", indent, file_name_colored));
+                scan.structure.push(format!("{}{}", indent, file_name_colored));
             }
         }
     }
@@ -1,26 +1,511 @@
 use colored::*;
 use std::io::{self, Write};
-use clap::Parser;
+use clap::{Parser, Subcommand};
 
 mod memory;
 mod shell;
 mod watcher;
 mod insight;
 mod coffee;
+mod activity;
+mod notifier;
+mod pomodoro;
+mod history;
+mod config;
+mod search;
+mod export;
+mod reminders;
+mod digest;
+mod changes;
+mod actions;
 
 use memory::*;
 use shell::*;
 use watcher::*;
 use insight::*;
 use coffee::*;
+use activity::*;
+use notifier::*;
+use pomodoro::*;
+use history::*;
+use config::*;
+use search::*;
+use export::*;
+use reminders::*;
+use digest::*;
+use changes::*;
+use std::sync::{Arc, Mutex};
+use std::path::Path;
+use std::time::Duration;
 
 #[derive(Parser)]
 #[command(name = "coffee-break")]
 #[command(about = "A friendly terminal assistant that reminds you to take coffee breaks", long_about = None)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Commands>,
+
     /// Coffee break interval in minutes (default: 60)
-    #[arg(short, long, default_value_t = 60)]
-    interval: u64,
+    #[arg(short, long)]
+    interval: Option<u64>,
+
+    /// Use Pomodoro cycles (work / short break / long break) instead of a fixed interval
+    #[arg(long, conflicts_with = "interval")]
+    pomodoro: bool,
+
+    /// Pomodoro work block length in minutes (default: 25)
+    #[arg(long)]
+    work: Option<u64>,
+
+    /// Pomodoro short break length in minutes (default: 5)
+    #[arg(long)]
+    short_break: Option<u64>,
+
+    /// Pomodoro long break length in minutes (default: 15)
+    #[arg(long)]
+    long_break: Option<u64>,
+
+    /// Take a long break after this many work blocks (default: 4)
+    #[arg(long)]
+    long_break_every: Option<u32>,
+
+    /// Minutes without file changes or REPL input that count as a break (0 = always count time; default: 5)
+    #[arg(long)]
+    idle_threshold: Option<u64>,
+
+    /// Where reminders are delivered; repeat or comma-separate to fan out (default: desktop)
+    #[arg(long = "notify", value_enum, value_delimiter = ',')]
+    notify: Option<Vec<SinkKind>>,
+
+    /// Shell command run on every notification (enables the 'command' sink)
+    #[arg(long)]
+    notify_command: Option<String>,
+
+    /// URL that receives every notification as a JSON POST (enables the 'webhook' sink)
+    #[arg(long)]
+    webhook_url: Option<String>,
+
+    /// Memory file (default: ~/.coffee_break_memory.json)
+    #[arg(long)]
+    memory_file: Option<String>,
+
+    /// Add a digest (open notes, changed files, focus time) to break reminders
+    #[arg(long)]
+    digest: bool,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Show break statistics (daily/weekly totals, streaks, focus blocks) and exit
+    Stats,
+}
+
+impl Args {
+    /// The command-line flags as the highest-precedence config layer.
+    fn config_layer(&self) -> ConfigLayer {
+        // An explicit --interval picks simple mode over a configured Pomodoro.
+        let pomodoro = if self.pomodoro {
+            Some(true)
+        } else {
+            self.interval.map(|_| false)
+        };
+
+        ConfigLayer {
+            interval: self.interval,
+            idle_threshold: self.idle_threshold,
+            pomodoro: PomodoroLayer {
+                enabled: pomodoro,
+                work: self.work,
+                short_break: self.short_break,
+                long_break: self.long_break,
+                long_break_every: self.long_break_every,
+            },
+            notify: NotifyLayer {
+                sinks: self.notify.clone(),
+                command: self.notify_command.clone(),
+                webhook_url: self.webhook_url.clone(),
+                ..Default::default()
+            },
+            memory: MemoryLayer {
+                file: self.memory_file.clone(),
+            },
+            digest: DigestLayer {
+                enabled: self.digest.then_some(true),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+}
+
+fn show_phase(timer: &BreakTimer) {
+    let state = timer.snapshot();
+    match state.cycle_position() {
+        Some((position, every)) => {
+            let phase = if state.phase.is_break() {
+                state.phase.label().bright_green()
+            } else {
+                state.phase.label().bright_red()
+            };
+            println!("🍅 Phase: {} ({} left)", phase.bold(), format_clock(state.remaining()).bright_white());
+            println!("🔁 Cycle: {} of {} until the long break", position, every);
+            println!("✅ Focus blocks completed: {}", state.cycle.to_string().bright_green());
+        }
+        None => {
+            println!(
+                "⏱  Simple mode: a reminder every {} ({} left)",
+                format_minutes(state.phase_length()),
+                format_clock(state.remaining()).bright_white()
+            );
+            println!("☕ Reminders so far: {}", state.cycle.to_string().bright_green());
+        }
+    }
+}
+
+fn show_status(timer: &BreakTimer, activity: &Activity, activity_aware: bool) {
+    let state = timer.snapshot();
+    let remaining = format_clock(state.remaining()).bright_white().bold();
+    if state.phase.is_break() {
+        println!("☕ {} ends in {}", state.phase.label(), remaining);
+    } else {
+        println!("⏱  Next break in {}", remaining);
+    }
+    if state.paused {
+        println!("{}", "⏸  Timer is paused (type 'resume' to continue)".bright_yellow());
+    }
+    if activity_aware {
+        if activity.is_idle() {
+            println!("💤 Idle for {}", format_clock(activity.idle_for()).bright_white());
+        } else {
+            println!("🧑‍💻 Actively coding for {}", format_clock(activity.active_for()).bright_white());
+        }
+    }
+}
+
+/// Handle the break timer commands. Returns `false` if `cmd` isn't one of them.
+fn handle_timer_command(cmd: &str, timer: &BreakTimer, activity: &Activity, activity_aware: bool) -> bool {
+    match cmd {
+        "pause" => {
+            timer.send(TimerCommand::Pause);
+            println!("{}", "⏸  Break timer paused".bright_yellow());
+        }
+        "resume" => {
+            timer.send(TimerCommand::Resume);
+            println!("{}", "▶️  Break timer resumed".bright_green());
+        }
+        "skip" => {
+            timer.send(TimerCommand::Skip);
+            if timer.snapshot().phase.is_break() {
+                println!("{}", "⏭  Break cut short".bright_yellow());
+            } else {
+                println!("{}", "⏭  Skipping the next break".bright_yellow());
+            }
+        }
+        "reset" => {
+            timer.send(TimerCommand::Reset);
+            println!("{}", "🔄 Break timer reset".bright_green());
+        }
+        "status" => show_status(timer, activity, activity_aware),
+        _ => {
+            if cmd != "snooze" && !cmd.starts_with("snooze ") {
+                return false;
+            }
+            let arg = cmd.trim_start_matches("snooze").trim();
+            let minutes = if arg.is_empty() { Ok(5) } else { arg.parse::<u64>() };
+            match minutes {
+                Ok(minutes) if minutes > 0 => {
+                    timer.send(TimerCommand::Snooze(Duration::from_secs(minutes * 60)));
+                    println!("😴 Snoozed: next break in {}", format_minutes(Duration::from_secs(minutes * 60)).bright_white());
+                }
+                _ => println!("{}", "Usage: snooze <minutes>".bright_red()),
+            }
+        }
+    }
+    true
+}
+
+/// Parse the `<id>` argument of a memory command, printing usage on failure.
+fn parse_memory_id(arg: &str, usage: &str) -> Option<u64> {
+    let id = arg.trim().trim_start_matches('#').parse().ok();
+    if id.is_none() {
+        println!("{}", format!("Usage: {}", usage).bright_red());
+    }
+    id
+}
+
+fn no_such_memory(id: u64) {
+    println!("{}", format!("❌ No memory with id {}", id).bright_red());
+}
+
+/// Split the leading `--global`, `--at <time>` and `--in <delay>` flags off
+/// a `remember` command; returns the scope flag, reminder time and the rest.
+fn parse_remember_flags(mut rest: &str) -> Result<(bool, Option<chrono::DateTime<chrono::Local>>, &str), String> {
+    let mut global = false;
+    let mut due = None;
+    loop {
+        rest = rest.trim_start();
+        let (flag, after) = rest.split_once(' ').unwrap_or((rest, ""));
+        match flag {
+            "--global" => global = true,
+            "--at" | "--in" => {
+                let after = after.trim_start();
+                let (value, after) = after.split_once(' ').unwrap_or((after, ""));
+                if value.is_empty() {
+                    return Err(format!("{} needs a value", flag));
+                }
+                due = Some(if flag == "--at" { parse_at(value)? } else { parse_in(value)? });
+                rest = after;
+                continue;
+            }
+            _ => return Ok((global, due, rest)),
+        }
+        rest = after;
+    }
+}
+
+fn save_memory(mem: &mut Memory) {
+    if let Err(err) = mem.save() {
+        println!("{}", format!("❌ {}", err).bright_red());
+    }
+}
+
+/// Handle the memory commands. Returns `false` if `cmd` isn't one of them.
+fn handle_memory_command(cmd: &str, mem: &mut Memory, project: &str) -> bool {
+    let (name, rest) = cmd.split_once(' ').unwrap_or((cmd, ""));
+    match name {
+        "remember" if !rest.is_empty() => {
+            let usage = "Usage: remember [--global] [--at 15:00 | --in 45m] [#tag ...] <text>";
+            let (global, due, rest) = match parse_remember_flags(rest) {
+                Ok(parsed) => parsed,
+                Err(err) => {
+                    println!("{}", format!("❌ {}", err).bright_red());
+                    println!("{}", usage.bright_red());
+                    return true;
+                }
+            };
+            let (text, tags) = parse_tags(rest);
+            if text.is_empty() {
+                println!("{}", usage.bright_red());
+                return true;
+            }
+            let scope = (!global).then(|| project.to_string());
+            let id = mem.add(text.clone(), tags, scope, due).id;
+            save_memory(mem);
+
+            let scope_note = if global { " (global)" } else { "" };
+            println!("💾 saved #{}{}: {}", id, scope_note, text.bright_green());
+            if let Some(due) = due {
+                println!("⏰ I'll remind you at {}", due.format("%Y-%m-%d %H:%M").to_string().bright_magenta());
+            }
+        }
+        "mem" if rest == "due" => print_due(mem),
+        "mem" if rest.is_empty() || rest == "--all" => {
+            let all = rest == "--all";
+            let entries = mem.sorted((!all).then_some(project));
+            if all {
+                println!("🧠 Memory (all projects):");
+            } else {
+                println!("🧠 Memory ({} + global):", Path::new(project).file_name().map(|n| n.to_string_lossy()).unwrap_or_default());
+            }
+            for entry in &entries {
+                print_entry(entry);
+            }
+            let hidden = mem.entries.len() - entries.len();
+            if hidden > 0 {
+                println!("{}", format!("   … {} note(s) from other projects hidden (mem --all)", hidden).bright_black());
+            }
+        }
+        "mem" if rest == "search" || rest.starts_with("search ") => {
+            match parse_search(rest.trim_start_matches("search"), project) {
+                Ok(query) => print_search_results(&query, &search(mem, &query)),
+                Err(err) => {
+                    println!("{}", format!("❌ {}", err).bright_red());
+                    println!("{}", "Usage: mem search <query> [--tag t] [--since 3d|YYYY-MM-DD] [--project p] [--done|--open] [--all]".bright_red());
+                }
+            }
+        }
+        "mem" if rest == "export" || rest.starts_with("export ") => {
+            let args = match parse_transfer_args(rest.trim_start_matches("export")) {
+                Ok(args) => args,
+                Err(err) => {
+                    println!("{}", format!("❌ {}", err).bright_red());
+                    println!("{}", "Usage: mem export [--format md|jsonl|csv] [--all] [path]".bright_red());
+                    return true;
+                }
+            };
+            let format = args
+                .format
+                .or_else(|| args.path.as_deref().and_then(ExportFormat::from_path))
+                .unwrap_or(ExportFormat::Markdown);
+            let entries = mem.sorted((!args.all).then_some(project));
+            let result = export_entries(&entries, format)
+                .and_then(|out| write_export(&out, args.path.as_deref()));
+            match (result, &args.path) {
+                (Ok(()), Some(path)) => println!("📤 exported {} memories to {}", entries.len(), path.display().to_string().bright_green()),
+                (Ok(()), None) => {}
+                (Err(err), _) => println!("{}", format!("❌ {}", err).bright_red()),
+            }
+        }
+        "mem" if rest == "import" || rest.starts_with("import ") => {
+            let usage = "Usage: mem import [--format md|jsonl|csv] <path>";
+            let args = match parse_transfer_args(rest.trim_start_matches("import")) {
+                Ok(args) if args.path.is_some() && !args.all => args,
+                Ok(_) => {
+                    println!("{}", usage.bright_red());
+                    return true;
+                }
+                Err(err) => {
+                    println!("{}", format!("❌ {}", err).bright_red());
+                    println!("{}", usage.bright_red());
+                    return true;
+                }
+            };
+            let path = args.path.unwrap();
+            let Some(format) = args.format.or_else(|| ExportFormat::from_path(&path)) else {
+                println!("{}", format!("❌ Can't tell the format of {}; pass --format md|jsonl|csv", path.display()).bright_red());
+                return true;
+            };
+            match read_import(&path, format) {
+                Ok(entries) => {
+                    let summary = mem.import(entries);
+                    save_memory(mem);
+                    println!(
+                        "📥 imported {} memories ({} duplicates skipped, {} renumbered)",
+                        summary.added.to_string().bright_green(),
+                        summary.duplicates,
+                        summary.renumbered
+                    );
+                }
+                Err(err) => println!("{}", format!("❌ {}", err).bright_red()),
+            }
+        }
+        "forget" => {
+            let Some(id) = parse_memory_id(rest, "forget <id>") else {
+                return true;
+            };
+            match mem.forget(id) {
+                Some(entry) => {
+                    save_memory(mem);
+                    println!("🗑️  forgot #{}: {}", id, entry.text.bright_yellow());
+                }
+                None => no_such_memory(id),
+            }
+        }
+        "edit" => {
+            let (id, text) = rest.split_once(' ').unwrap_or((rest, ""));
+            let Some(id) = parse_memory_id(id, "edit <id> <text>") else {
+                return true;
+            };
+            let (text, tags) = parse_tags(text);
+            if text.is_empty() {
+                println!("{}", "Usage: edit <id> <text>".bright_red());
+                return true;
+            }
+            if mem.edit(id, text, tags).is_some() {
+                save_memory(mem);
+                println!("✏️  updated #{}", id);
+            } else {
+                no_such_memory(id);
+            }
+        }
+        "done" => {
+            let Some(id) = parse_memory_id(rest, "done <id>") else {
+                return true;
+            };
+            match mem.toggle_done(id) {
+                Some(true) => {
+                    save_memory(mem);
+                    println!("✅ #{} marked done", id);
+                }
+                Some(false) => {
+                    save_memory(mem);
+                    println!("↩️  #{} reopened", id);
+                }
+                None => no_such_memory(id),
+            }
+        }
+        "pin" => {
+            let Some(id) = parse_memory_id(rest, "pin <id>") else {
+                return true;
+            };
+            match mem.toggle_pin(id) {
+                Some(true) => {
+                    save_memory(mem);
+                    println!("📌 #{} pinned", id);
+                }
+                Some(false) => {
+                    save_memory(mem);
+                    println!("📍 #{} unpinned", id);
+                }
+                None => no_such_memory(id),
+            }
+        }
+        "undo" if rest.is_empty() => match mem.undo() {
+            Some(action) => {
+                save_memory(mem);
+                println!("↩️  undid '{}'", action);
+            }
+            None => println!("{}", "Nothing to undo".bright_yellow()),
+        },
+        _ => return false,
+    }
+    true
+}
+
+/// Handle `changes` and the `watch` switches. Returns `false` if `cmd`
+/// isn't one of them.
+fn handle_watch_command(cmd: &str, control: &WatchControl, history: &Mutex<History>) -> bool {
+    let (name, rest) = cmd.split_once(' ').unwrap_or((cmd, ""));
+    match (name, rest.trim()) {
+        ("changes", "") => {
+            let since = history.lock().unwrap().last_break();
+            let label = match since {
+                Some(since) => format!("since the last break ({})", since.format("%H:%M")),
+                None => "this session".to_string(),
+            };
+            print_changes(&control.log.since(since), &label);
+        }
+        ("changes", when) => {
+            // A bare number is minutes.
+            let when = if when.chars().all(|c| c.is_ascii_digit()) {
+                format!("{}m", when)
+            } else {
+                when.to_string()
+            };
+            match parse_since(&when) {
+                Ok(since) => print_changes(&control.log.since(Some(since)), &format!("in the last {}", when)),
+                Err(err) => {
+                    println!("{}", format!("❌ {}", err).bright_red());
+                    println!("{}", "Usage: changes [minutes | 30m | 2h | 1d]".bright_red());
+                }
+            }
+        }
+        ("watch", "") => {
+            let state = if control.is_enabled() { "on".bright_green() } else { "off".bright_red() };
+            let output = if control.is_verbose() { "verbose" } else { "quiet" };
+            println!("👀 File watcher is {} ({}); see what changed with 'changes'", state, output);
+        }
+        ("watch", "on") => {
+            control.set_enabled(true);
+            println!("👀 File watcher on");
+        }
+        ("watch", "off") => {
+            control.set_enabled(false);
+            println!("🙈 File watcher off; changes are no longer recorded or counted as activity");
+        }
+        ("watch", "verbose") => {
+            let verbose = !control.is_verbose();
+            control.set_verbose(verbose);
+            if verbose {
+                println!("📢 Printing a line for every batch of file changes");
+            } else {
+                println!("🤫 Recording file changes quietly; see them with 'changes'");
+            }
+        }
+        ("watch", _) => println!("{}", "Usage: watch [on|off|verbose]".bright_red()),
+        _ => return false,
+    }
+    true
 }
 
 fn show_help() {
@@ -30,16 +515,42 @@ fn show_help() {
 
     println!("{}", "📝 Built-in Commands:".bright_yellow().bold());
     println!("  {}  {} - Show this help message", "help".bright_cyan(), "(or ? or h)".bright_white());
-    println!("  {}  {} - Save information to persistent memory", "remember <text>".bright_cyan(), "💾".bright_green());
-    println!("  {}  {} - Display all saved memories", "mem".bright_cyan(), "🧠".bright_green());
+    println!("  {}  {} - Save a note for this project (#words become tags)", "remember <text>".bright_cyan(), "💾".bright_green());
+    println!("  {}  {} - Save a note visible in every project", "remember --global <text>".bright_cyan(), "🌐".bright_green());
+    println!("  {}  {} - Save a note and get a reminder at that time", "remember --at 15:00 | --in 45m <text>".bright_cyan(), "⏰".bright_green());
+    println!("  {}  {} - Show this project's and global memories", "mem".bright_cyan(), "🧠".bright_green());
+    println!("  {}  {} - Show memories from every project", "mem --all".bright_cyan(), "🗂️".bright_green());
+    println!("  {}  {} - Fuzzy search (--tag, --since, --project, --done/--open)", "mem search <query>".bright_cyan(), "🔎".bright_green());
+    println!("  {}  {} - List upcoming and delivered reminders", "mem due".bright_cyan(), "⏰".bright_green());
+    println!("  {}  {} - Export memories (--all for every project)", "mem export [--format md|jsonl|csv] [path]".bright_cyan(), "📤".bright_green());
+    println!("  {}  {} - Import memories, skipping duplicates", "mem import <path>".bright_cyan(), "📥".bright_green());
+    println!("  {}  {} - Delete a memory", "forget <id>".bright_cyan(), "🗑️".bright_green());
+    println!("  {}  {} - Replace the text (and #tags) of a memory", "edit <id> <text>".bright_cyan(), "✏️".bright_green());
+    println!("  {}  {} - Mark a memory done (again to reopen)", "done <id>".bright_cyan(), "✅".bright_green());
+    println!("  {}  {} - Pin a memory to the top of the list (again to unpin)", "pin <id>".bright_cyan(), "📌".bright_green());
+    println!("  {}  {} - Revert the last memory change", "undo".bright_cyan(), "↩️".bright_green());
+    println!("  {}  {} - Files changed since the last break (or in the last N minutes)", "changes [N | 2h]".bright_cyan(), "📜".bright_green());
+    println!("  {}  {} - Pause or resume the file watcher, or show its state", "watch [on|off]".bright_cyan(), "👀".bright_green());
+    println!("  {}  {} - Toggle printing a line for every batch of changes", "watch verbose".bright_cyan(), "📢".bright_green());
     println!("  {}  {} - Analyze current project structure", "insight".bright_cyan(), "🔍".bright_green());
     println!("  {}  {} - Trigger coffee dance animation", "coffee".bright_cyan(), "☕".bright_green());
+    println!("  {}  {} - Show the current Pomodoro phase and cycle", "phase".bright_cyan(), "🍅".bright_green());
+    println!("  {}  {} - Show time remaining until the next break", "status".bright_cyan(), "⏱️".bright_green());
+    println!("  {}  {} - Pause / resume the break timer", "pause | resume".bright_cyan(), "⏸️".bright_green());
+    println!("  {}  {} - Remind me again in <min> minutes (default 5)", "snooze <min>".bright_cyan(), "😴".bright_green());
+    println!("  {}  {} - Skip the next break, or end the current one", "skip".bright_cyan(), "⏭️".bright_green());
+    println!("  {}  {} - Restart the break timer from scratch", "reset".bright_cyan(), "🔄".bright_green());
+    println!("  {}  {} - Break statistics: totals, streaks, focus blocks", "stats".bright_cyan(), "📈".bright_green());
+    println!("  {}  {} - Show effective settings and where each came from", "config".bright_cyan(), "⚙️".bright_green());
     println!("  {}  {} - AI explanation (placeholder)", "explain <topic>".bright_cyan(), "🔮".bright_green());
     println!("  {}  {} - Exit Coffee Break Terminal", "bye".bright_cyan(), "👋".bright_green());
 
     println!("\n{}", "☕ Coffee Break Features:".bright_yellow().bold());
     println!("  • {} - Automatic coffee break reminders (configurable via --interval)", "Auto Reminders".bright_green());
-    println!("  • {} - System notifications (macOS/Windows)", "System Popups".bright_green());
+    println!("  • {} - Work / short break / long break cycles (--pomodoro)", "Pomodoro Mode".bright_green());
+    println!("  • {} - Idle time counts as a break (--idle-threshold)", "Activity Aware".bright_green());
+    println!("  • {} - System notifications (macOS/Windows/Linux)", "System Popups".bright_green());
+    println!("  • {} - Fan out to terminal, desktop, bell, a shell hook or a webhook (--notify)", "Notification Sinks".bright_green());
     println!("  • {} - Beautiful dialog with coffee icon", "Visual Dialog".bright_green());
     println!("  • {} - 20-second coffee dance animation", "Coffee Animation".bright_green());
 
@@ -64,7 +575,12 @@ fn show_help() {
 
 fn main() {
     let args = Args::parse();
-    
+
+    if let Some(Commands::Stats) = args.command {
+        print_stats(&History::load().stats());
+        return;
+    }
+
     println!("{}", r#"
   ██████╗ ██████╗ ███████╗███████╗███████╗███████╗    ██████╗ ██████╗ ███████╗ █████╗ ██╗  ██╗
  ██╔═══██╗██╔══██╗██╔════╝██╔════╝██╔════╝██╔════╝    ██╔══██╗██╔══██╗██╔════╝██╔══██╗██║ ██╔╝
@@ -74,16 +590,91 @@ fn main() {
                                                                                             v0.1
 "#.bright_cyan());
 
+    let config = match Config::load(args.config_layer()) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{} {}", "❌".red(), err.red());
+            std::process::exit(2);
+        }
+    };
+
     println!("{}", "☕ Coffee Break Terminal initialized and watching your workspace…\n".bright_green());
-    println!("{}", format!("☕ Coffee break reminders: every {} minute(s), then 20 sec animation!\n", args.interval).bright_yellow());
+    let plan = config.break_plan();
+    if let BreakPlan::Pomodoro { work, short_break, long_break, long_break_every } = &plan {
+        println!("{}", format!(
+            "🍅 Pomodoro mode: {} work, {} short break, {} long break every {} cycles\n",
+            format_minutes(*work), format_minutes(*short_break), format_minutes(*long_break), long_break_every
+        ).bright_yellow());
+    } else {
+        println!("{}", format!("☕ Coffee break reminders: every {} minute(s), then 20 sec animation!\n", config.interval.value).bright_yellow());
+    }
+
+    let notifiers = match Notifiers::from_selection(
+        &config.notify.value,
+        config.notify_command.value.clone(),
+        config.webhook_url.value.clone(),
+        &config.icon_paths.value,
+    ) {
+        Ok(n) => Arc::new(n),
+        Err(err) => {
+            eprintln!("{} {}", "❌".red(), err.red());
+            std::process::exit(2);
+        }
+    };
+    println!("{}", format!("🔔 Notifications: {}\n", notifiers.names().join(", ")).bright_yellow());
 
-    let mut mem = Memory::load();
+    let mem = match Memory::load(config.memory_file.value.clone()) {
+        Ok(mem) => Arc::new(Mutex::new(mem)),
+        Err(err) => {
+            eprintln!("{} {}", "❌".red(), err.red());
+            std::process::exit(2);
+        }
+    };
+    let project = project_root(Path::new(".")).display().to_string();
+    start_reminders(Arc::clone(&mem), Arc::clone(&notifiers));
+
+    let activity = Arc::new(Activity::new(Duration::from_secs(config.idle_threshold.value * 60)));
+    let activity_aware = config.idle_threshold.value > 0;
+    if activity_aware {
+        println!("{}", format!(
+            "🧑‍💻 Only active coding time counts; {} idle counts as a break\n",
+            format_minutes(activity.idle_threshold())
+        ).bright_yellow());
+    }
 
     // Start file watcher thread
-    std::thread::spawn(move || watch_folder("./"));
+    let watcher_activity = Arc::clone(&activity);
+    let watch_options = WatchOptions {
+        poll_interval: Duration::from_millis(config.watch_poll_ms.value),
+        debounce: Duration::from_millis(config.watch_debounce_ms.value),
+        ignore: config.watch_ignore.value.clone(),
+        actions: config.watch_actions.value.clone(),
+        confirm_commands: config.confirm_commands.value.clone(),
+    };
+    let watch_control = Arc::new(WatchControl::new(
+        config.watch_verbose.value,
+        ChangeLog::new(config.watch_log_file.value.clone()),
+    ));
+    let watcher_control = Arc::clone(&watch_control);
+    std::thread::spawn(move || watch_folder("./", watcher_activity, watch_options, watcher_control));
 
     // Start coffee dance (runs in background thread) with specified interval
-    start_coffee_dance(args.interval);
+    let history = Arc::new(Mutex::new(History::load()));
+    history.lock().unwrap().record(EventKind::SessionStart, None);
+    let session_start = std::time::Instant::now();
+
+    let timer = start_coffee_dance(
+        plan,
+        Arc::clone(&notifiers),
+        activity_aware.then(|| Arc::clone(&activity)),
+        Arc::clone(&history),
+        config.digest.value.then(|| DigestSource {
+            memory: Arc::clone(&mem),
+            project: project.clone(),
+            activity: Arc::clone(&activity),
+            max_items: config.digest_max_items.value,
+        }),
+    );
 
     // Main REPL loop
     loop {
@@ -94,28 +685,27 @@ fn main() {
         io::stdin().read_line(&mut input).unwrap();
 
         let cmd = input.trim();
+        activity.touch();
 
         // exit
         if cmd == "bye" {
+            history.lock().unwrap().record(EventKind::SessionEnd, Some(session_start.elapsed()));
             println!("{}", "👋 See you later!".bright_yellow());
             break;
         }
 
-        // Coffee Break's own commands
-        if cmd.starts_with("remember ") {
-            let data = cmd.trim_start_matches("remember ").to_string();
-            mem.data.push(data.clone());
-            mem.save();
+        if cmd == "config" {
+            print_config(&config);
+            continue;
+        }
 
-            println!("💾 saved: {}", data.bright_green());
+        if cmd == "stats" {
+            print_stats(&history.lock().unwrap().stats());
             continue;
         }
 
-        if cmd == "mem" {
-            println!("🧠 Memory:");
-            for (i, item) in mem.data.iter().enumerate() {
-                println!("{} {}", i, item.bright_yellow());
-            }
+        // Coffee Break's own commands
+        if handle_memory_command(cmd, &mut mem.lock().unwrap(), &project) {
             continue;
         }
 
@@ -126,14 +716,27 @@ fn main() {
             continue;
         }
 
+        if handle_watch_command(cmd, &watch_control, &history) {
+            continue;
+        }
+
         if cmd == "insight" {
-            let insight = analyze_project("./");
+            let insight = analyze_project("./", &config.insight_ignore.value);
             print_insight(&insight);
             continue;
         }
 
         if cmd == "coffee" {
-            show_coffee_dance();
+            show_coffee_dance(Arc::clone(&notifiers));
+            continue;
+        }
+
+        if cmd == "phase" {
+            show_phase(&timer);
+            continue;
+        }
+
+        if handle_timer_command(cmd, &timer, &activity, activity_aware) {
             continue;
         }
 
@@ -143,7 +746,7 @@ fn main() {
         }
 
         // Execute shell commands (with safety layer)
-        run_smart_shell(cmd);
+        run_smart_shell(cmd, &config.confirm_commands.value);
     }
 }
 
@@ -1,47 +1,566 @@
-use chrono::Utc;
+use chrono::{DateTime, Local, Utc};
+use colored::*;
 use dirs::home_dir;
 use serde::{Serialize, Deserialize};
-use std::{fs, path::PathBuf};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Current on-disk format. Version 1 was a bare `{ data: [String], last_updated }`.
+const FORMAT_VERSION: u32 = 2;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MemoryEntry {
+    pub id: u64,
+    pub text: String,
+    pub created_at: String,
+    pub updated_at: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Root of the project the note belongs to; `None` for global notes.
+    #[serde(default)]
+    pub project: Option<String>,
+    #[serde(default)]
+    pub done: bool,
+    #[serde(default)]
+    pub pinned: bool,
+    /// When to send a reminder about this note (RFC 3339).
+    #[serde(default)]
+    pub due: Option<String>,
+    /// Set once the reminder has been delivered.
+    #[serde(default)]
+    pub reminded: bool,
+}
+
+impl MemoryEntry {
+    pub fn created_local(&self) -> Option<DateTime<Local>> {
+        DateTime::parse_from_rfc3339(&self.created_at)
+            .ok()
+            .map(|t| t.with_timezone(&Local))
+    }
+
+    pub fn due_local(&self) -> Option<DateTime<Local>> {
+        self.due
+            .as_deref()
+            .and_then(|d| DateTime::parse_from_rfc3339(d).ok())
+            .map(|t| t.with_timezone(&Local))
+    }
+
+    /// A reminder that should fire now: its time has passed, it hasn't been
+    /// delivered yet and the note is still open.
+    pub fn is_due(&self, now: DateTime<Local>) -> bool {
+        !self.reminded && !self.done && self.due_local().is_some_and(|due| due <= now)
+    }
+
+    /// Whether the note is visible from `project`: its own notes plus global ones.
+    pub fn in_scope(&self, project: &str) -> bool {
+        self.project.as_deref().is_none_or(|p| p == project)
+    }
+
+    /// Last path component of the project directory, for compact display.
+    pub fn project_name(&self) -> Option<&str> {
+        self.project
+            .as_deref()
+            .map(|p| Path::new(p).file_name().and_then(|n| n.to_str()).unwrap_or(p))
+    }
+}
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Memory {
-    pub data: Vec<String>,
+    pub version: u32,
+    pub entries: Vec<MemoryEntry>,
+    pub next_id: u64,
     pub last_updated: String,
+    #[serde(skip)]
+    path: PathBuf,
+    /// State before the last mutation, for a one-level `undo`.
+    #[serde(skip)]
+    undo: Option<UndoPoint>,
+    /// `updated_at` of every entry as of the last load or save, used to tell
+    /// our own changes from those made by other sessions.
+    #[serde(skip)]
+    base: HashMap<u64, String>,
+}
+
+#[derive(Debug, Default)]
+pub struct ImportSummary {
+    pub added: usize,
+    pub duplicates: usize,
+    /// Imported notes whose id was already taken here.
+    pub renumbered: usize,
+}
+
+#[derive(Debug)]
+struct UndoPoint {
+    action: String,
+    entries: Vec<MemoryEntry>,
+    next_id: u64,
+}
+
+/// The flat list of strings written by older versions.
+#[derive(Deserialize)]
+struct LegacyMemory {
+    data: Vec<String>,
+    last_updated: String,
 }
 
 impl Memory {
-    pub fn path() -> PathBuf {
+    pub fn default_path() -> PathBuf {
         let mut p = home_dir().unwrap();
         p.push(".coffee_break_memory.json");
         p
     }
 
-    pub fn load() -> Self {
-        let path = Self::path();
-        if path.exists() {
-            let raw = fs::read_to_string(path).ok();
-            if let Some(json) = raw {
-                serde_json::from_str(&json).unwrap_or_else(|_| Self::empty())
-            } else {
-                Self::empty()
-            }
+    /// Load the store at `path`. A missing file gives an empty store; a file
+    /// that can't be read or parsed is an error, so it never gets overwritten.
+    pub fn load(path: PathBuf) -> Result<Self, String> {
+        let (mut mem, migrated) = if path.exists() {
+            let json = fs::read_to_string(&path)
+                .map_err(|e| format!("Can't read memory file {}: {}", path.display(), e))?;
+            Self::parse(&json, &path).ok_or_else(|| unparsable(&path))?
         } else {
-            Self::empty()
+            (Self::empty(), false)
+        };
+        mem.path = path;
+        if migrated {
+            let _lock = mem.lock()?;
+            mem.write()?;
         }
+        mem.base = mem.snapshot();
+        Ok(mem)
+    }
+
+    /// Parse either format; the flag is set when a legacy file was converted.
+    /// The original legacy contents are kept next to the file as `<name>.json.v1.bak`.
+    fn parse(json: &str, path: &Path) -> Option<(Self, bool)> {
+        if let Ok(mem) = serde_json::from_str::<Memory>(json) {
+            return Some((mem, false));
+        }
+        let legacy: LegacyMemory = serde_json::from_str(json).ok()?;
+        let _ = fs::write(path.with_extension("json.v1.bak"), json);
+
+        let mut mem = Self::empty();
+        for text in legacy.data {
+            let id = mem.next_id;
+            mem.next_id += 1;
+            mem.entries.push(MemoryEntry {
+                id,
+                text,
+                created_at: legacy.last_updated.clone(),
+                updated_at: legacy.last_updated.clone(),
+                tags: vec![],
+                project: None,
+                done: false,
+                pinned: false,
+                due: None,
+                reminded: false,
+            });
+        }
+        Some((mem, true))
     }
 
     pub fn empty() -> Self {
         Self {
-            data: vec![],
+            version: FORMAT_VERSION,
+            entries: vec![],
+            next_id: 1,
             last_updated: Utc::now().to_rfc3339(),
+            path: Self::default_path(),
+            undo: None,
+            base: HashMap::new(),
         }
     }
 
-    pub fn save(&mut self) {
+    /// Persist the store. Under an exclusive lock, changes other sessions
+    /// saved since we last synced are merged in, the previous file is kept as
+    /// `<file>.bak` and the new contents are written atomically.
+    pub fn save(&mut self) -> Result<(), String> {
+        self.save_with(|_| ())
+    }
+
+    /// Like `save`, but applies `change` to the merged store before writing,
+    /// so it sees what other sessions saved.
+    fn save_with<T>(&mut self, change: impl FnOnce(&mut Self) -> T) -> Result<T, String> {
+        let _lock = self.lock()?;
+        if let Some(disk) = self.read_disk()? {
+            self.merge(disk);
+        }
+        let result = change(self);
+        self.write()?;
+        self.base = self.snapshot();
+        Ok(result)
+    }
+
+    /// Mark every reminder that is due as delivered and return those notes.
+    /// Done under the store's lock, so with several sessions running each
+    /// reminder is only handed to one of them.
+    pub fn claim_due(&mut self, now: DateTime<Local>) -> Result<Vec<MemoryEntry>, String> {
+        if !self.entries.iter().any(|e| e.is_due(now)) {
+            return Ok(vec![]);
+        }
+        self.save_with(|mem| {
+            let stamp = Utc::now().to_rfc3339();
+            let mut due = Vec::new();
+            for entry in mem.entries.iter_mut().filter(|e| e.is_due(now)) {
+                entry.reminded = true;
+                entry.updated_at = stamp.clone();
+                due.push(entry.clone());
+            }
+            due
+        })
+    }
+
+    /// Notes with a pending or delivered reminder that are still open,
+    /// soonest first.
+    pub fn with_due(&self) -> Vec<&MemoryEntry> {
+        let mut entries: Vec<&MemoryEntry> = self
+            .entries
+            .iter()
+            .filter(|e| !e.done && e.due.is_some())
+            .collect();
+        entries.sort_by_key(|e| e.due_local());
+        entries
+    }
+
+    fn snapshot(&self) -> HashMap<u64, String> {
+        self.entries.iter().map(|e| (e.id, e.updated_at.clone())).collect()
+    }
+
+    /// Block until we hold the store's lock file; released when dropped.
+    fn lock(&self) -> Result<File, String> {
+        let path = sidecar(&self.path, "lock");
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .map_err(|e| format!("Can't open lock file {}: {}", path.display(), e))?;
+        file.lock()
+            .map_err(|e| format!("Can't lock {}: {}", path.display(), e))?;
+        Ok(file)
+    }
+
+    /// The store as currently saved on disk, if there is one.
+    fn read_disk(&self) -> Result<Option<Memory>, String> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+        let json = fs::read_to_string(&self.path)
+            .map_err(|e| format!("Can't read memory file {}: {}", self.path.display(), e))?;
+        serde_json::from_str(&json)
+            .map(Some)
+            .map_err(|_| unparsable(&self.path))
+    }
+
+    /// Back up the current file, then write to a temp file and rename it
+    /// over the original so a crash never leaves a half-written store.
+    fn write(&mut self) -> Result<(), String> {
         self.last_updated = Utc::now().to_rfc3339();
         let raw = serde_json::to_string_pretty(self).unwrap();
-        let _ = fs::write(Self::path(), raw);
+        let failed = |e: std::io::Error| format!("Can't save memory to {}: {}", self.path.display(), e);
+
+        if self.path.exists() {
+            fs::copy(&self.path, sidecar(&self.path, "bak")).map_err(failed)?;
+        }
+        let tmp = sidecar(&self.path, &format!("tmp.{}", std::process::id()));
+        let mut file = File::create(&tmp).map_err(failed)?;
+        file.write_all(raw.as_bytes()).map_err(failed)?;
+        file.sync_all().map_err(failed)?;
+        fs::rename(&tmp, &self.path).map_err(failed)
     }
+
+    /// Three-way merge of our entries with what another session saved,
+    /// using `base` as the common ancestor. Entries changed on only one
+    /// side take that side's version; when both changed, the newer wins.
+    fn merge(&mut self, disk: Memory) {
+        let ours: HashMap<u64, &MemoryEntry> = self.entries.iter().map(|e| (e.id, e)).collect();
+        let changed_by_us = |e: &MemoryEntry| self.base.get(&e.id) != Some(&e.updated_at);
+        let mut merged = Vec::new();
+        let mut collisions = Vec::new();
+
+        for theirs in &disk.entries {
+            match (self.base.get(&theirs.id), ours.get(&theirs.id)) {
+                (Some(base), Some(ours)) => {
+                    let changed_by_them = theirs.updated_at != *base;
+                    if changed_by_us(ours) && (!changed_by_them || ours.updated_at >= theirs.updated_at) {
+                        merged.push((*ours).clone());
+                    } else {
+                        merged.push(theirs.clone());
+                    }
+                }
+                // We deleted it; keep it only if they edited it meanwhile.
+                (Some(base), None) => {
+                    if theirs.updated_at != *base {
+                        merged.push(theirs.clone());
+                    }
+                }
+                // Both sides created a note with this id.
+                (None, Some(ours)) => {
+                    merged.push(theirs.clone());
+                    collisions.push((*ours).clone());
+                }
+                (None, None) => merged.push(theirs.clone()),
+            }
+        }
+
+        let on_disk: HashSet<u64> = disk.entries.iter().map(|e| e.id).collect();
+        for entry in &self.entries {
+            // Missing on disk: new on our side, or deleted by them (dropped
+            // unless we changed it since).
+            if !on_disk.contains(&entry.id) && changed_by_us(entry) {
+                merged.push(entry.clone());
+            }
+        }
+
+        let max_id = merged.iter().map(|e| e.id).max().unwrap_or(0);
+        self.next_id = self.next_id.max(disk.next_id).max(max_id + 1);
+        for mut entry in collisions {
+            let old = entry.id;
+            entry.id = self.next_id;
+            self.next_id += 1;
+            println!(
+                "{}",
+                format!("⚠️  #{} was also used by another session; your note is now #{}", old, entry.id).bright_yellow()
+            );
+            merged.push(entry);
+        }
+
+        merged.sort_by_key(|e| e.id);
+        self.entries = merged;
+    }
+
+    fn checkpoint(&mut self, action: &str) {
+        self.undo = Some(UndoPoint {
+            action: action.to_string(),
+            entries: self.entries.clone(),
+            next_id: self.next_id,
+        });
+    }
+
+    /// Skip ids another session has handed out since we last synced.
+    fn sync_next_id(&mut self) {
+        if let Ok(Some(disk)) = self.read_disk() {
+            self.next_id = self.next_id.max(disk.next_id);
+        }
+    }
+
+    fn entry_mut(&mut self, id: u64) -> Option<&mut MemoryEntry> {
+        self.entries.iter_mut().find(|e| e.id == id)
+    }
+
+    pub fn add(
+        &mut self,
+        text: String,
+        tags: Vec<String>,
+        project: Option<String>,
+        due: Option<DateTime<Local>>,
+    ) -> &MemoryEntry {
+        self.checkpoint("remember");
+        self.sync_next_id();
+        let now = Utc::now().to_rfc3339();
+        let id = self.next_id;
+        self.next_id += 1;
+        self.entries.push(MemoryEntry {
+            id,
+            text,
+            created_at: now.clone(),
+            updated_at: now,
+            tags,
+            project,
+            done: false,
+            pinned: false,
+            due: due.map(|d| d.with_timezone(&Utc).to_rfc3339()),
+            reminded: false,
+        });
+        self.entries.last().unwrap()
+    }
+
+    pub fn forget(&mut self, id: u64) -> Option<MemoryEntry> {
+        let index = self.entries.iter().position(|e| e.id == id)?;
+        self.checkpoint("forget");
+        Some(self.entries.remove(index))
+    }
+
+    /// Replace the text of a memory. Tags in the new text replace the old
+    /// tags; without any, the old tags are kept.
+    pub fn edit(&mut self, id: u64, text: String, tags: Vec<String>) -> Option<&MemoryEntry> {
+        self.entry_mut(id)?;
+        self.checkpoint("edit");
+        let entry = self.entry_mut(id)?;
+        entry.text = text;
+        if !tags.is_empty() {
+            entry.tags = tags;
+        }
+        entry.updated_at = Utc::now().to_rfc3339();
+        Some(entry)
+    }
+
+    /// Toggle the done flag; returns the new value.
+    pub fn toggle_done(&mut self, id: u64) -> Option<bool> {
+        self.entry_mut(id)?;
+        self.checkpoint("done");
+        let entry = self.entry_mut(id)?;
+        entry.done = !entry.done;
+        entry.updated_at = Utc::now().to_rfc3339();
+        Some(entry.done)
+    }
+
+    /// Toggle the pinned flag; returns the new value.
+    pub fn toggle_pin(&mut self, id: u64) -> Option<bool> {
+        self.entry_mut(id)?;
+        self.checkpoint("pin");
+        let entry = self.entry_mut(id)?;
+        entry.pinned = !entry.pinned;
+        entry.updated_at = Utc::now().to_rfc3339();
+        Some(entry.pinned)
+    }
+
+    /// Add entries from an export, keeping their ids, timestamps and tags.
+    /// Notes whose text already exists in the same project are skipped;
+    /// ids that are taken (or missing) are replaced with fresh ones.
+    pub fn import(&mut self, entries: Vec<MemoryEntry>) -> ImportSummary {
+        let previous_undo = self.undo.take();
+        self.checkpoint("import");
+        self.sync_next_id();
+        let incoming_max = entries.iter().map(|e| e.id).max().unwrap_or(0);
+        self.next_id = self.next_id.max(incoming_max + 1);
+
+        let mut seen: HashSet<(String, Option<String>)> = self.entries.iter().map(dedupe_key).collect();
+        let mut ids: HashSet<u64> = self.entries.iter().map(|e| e.id).collect();
+        let mut summary = ImportSummary::default();
+        for mut entry in entries {
+            if !seen.insert(dedupe_key(&entry)) {
+                summary.duplicates += 1;
+                continue;
+            }
+            if entry.id == 0 || ids.contains(&entry.id) {
+                if entry.id != 0 {
+                    summary.renumbered += 1;
+                }
+                entry.id = self.next_id;
+                self.next_id += 1;
+            }
+            ids.insert(entry.id);
+            self.entries.push(entry);
+            summary.added += 1;
+        }
+        if summary.added == 0 {
+            self.undo = previous_undo;
+        }
+        self.entries.sort_by_key(|e| e.id);
+        summary
+    }
+
+    /// Revert the last mutation; returns the name of the undone action.
+    pub fn undo(&mut self) -> Option<String> {
+        let point = self.undo.take()?;
+        self.entries = point.entries;
+        self.next_id = point.next_id;
+        Some(point.action)
+    }
+
+    /// Entries for display: pinned first, then in id order. With a
+    /// `project`, only that project's notes and global notes are included.
+    pub fn sorted(&self, project: Option<&str>) -> Vec<&MemoryEntry> {
+        let mut entries: Vec<&MemoryEntry> = self
+            .entries
+            .iter()
+            .filter(|e| project.is_none_or(|p| e.in_scope(p)))
+            .collect();
+        entries.sort_by_key(|e| (!e.pinned, e.id));
+        entries
+    }
+}
+
+/// Notes count as duplicates when their text matches (ignoring case and
+/// spacing) within the same project.
+fn dedupe_key(entry: &MemoryEntry) -> (String, Option<String>) {
+    let text = entry.text.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+    (text, entry.project.clone())
+}
+
+/// `memory.json` → `memory.json.<suffix>`
+fn sidecar(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", suffix));
+    PathBuf::from(name)
+}
+
+fn unparsable(path: &Path) -> String {
+    format!(
+        "Memory file {} is corrupted and can't be parsed. It was left untouched; \
+         fix it or restore the previous version from {}",
+        path.display(),
+        sidecar(path, "bak").display()
+    )
 }
 
+/// The project a directory belongs to: the nearest ancestor holding `.git`
+/// or a `.coffee-break.toml`, falling back to the directory itself.
+pub fn project_root(dir: &Path) -> PathBuf {
+    let dir = fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+    dir.ancestors()
+        .find(|d| d.join(".git").exists() || d.join(".coffee-break.toml").exists())
+        .map(Path::to_path_buf)
+        .unwrap_or(dir)
+}
 
+/// Split `#tags` out of free text: `"#bug fix parser"` → `("fix parser", ["bug"])`.
+pub fn parse_tags(input: &str) -> (String, Vec<String>) {
+    let mut tags = Vec::new();
+    let mut words = Vec::new();
+    for word in input.split_whitespace() {
+        match word.strip_prefix('#') {
+            Some(tag) if !tag.is_empty() => {
+                let tag = tag.to_lowercase();
+                if !tags.contains(&tag) {
+                    tags.push(tag);
+                }
+            }
+            _ => words.push(word),
+        }
+    }
+    (words.join(" "), tags)
+}
+
+pub fn print_entry(entry: &MemoryEntry) {
+    let text = if entry.done {
+        entry.text.bright_black().strikethrough().to_string()
+    } else {
+        entry.text.bright_yellow().to_string()
+    };
+    print_entry_with_text(entry, &text);
+}
+
+/// Print a memory line with `text` already rendered (e.g. with highlights).
+pub fn print_entry_with_text(entry: &MemoryEntry, text: &str) {
+    let check = if entry.done { "[x]".bright_green() } else { "[ ]".bright_white() };
+    let pin = if entry.pinned { "📌 " } else { "" };
+    let tags: String = entry.tags.iter().map(|t| format!(" #{}", t)).collect();
+
+    let mut meta = Vec::new();
+    if let Some(created) = entry.created_local() {
+        meta.push(created.format("%Y-%m-%d %H:%M").to_string());
+    }
+    match entry.project_name() {
+        Some(project) => meta.push(project.to_string()),
+        None => meta.push("global".to_string()),
+    }
+    let due = match entry.due_local() {
+        Some(due) if entry.reminded => format!(" 🔔 {}", due.format("%Y-%m-%d %H:%M")).bright_black(),
+        Some(due) => format!(" ⏰ {}", due.format("%Y-%m-%d %H:%M")).bright_magenta(),
+        None => "".normal(),
+    };
+
+    println!(
+        "{:>4} {} {}{}{}{} {}",
+        entry.id.to_string().bright_cyan(),
+        check,
+        pin,
+        text,
+        tags.bright_magenta(),
+        due,
+        format!("({})", meta.join(", ")).bright_black()
+    );
+}
@@ -0,0 +1,557 @@
+use clap::ValueEnum;
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::Command;
+
+/// What a notification is about, so sinks can decide how loud to be.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    /// Time to stop and take a break.
+    BreakReminder,
+    /// The break is over; back to work.
+    BreakOver,
+    /// A memory's reminder time has come.
+    MemoryDue,
+    /// Anything informational (animation started, status changes, ...).
+    Info,
+}
+
+impl NotificationKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            NotificationKind::BreakReminder => "break_reminder",
+            NotificationKind::BreakOver => "break_over",
+            NotificationKind::MemoryDue => "memory_due",
+            NotificationKind::Info => "info",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Notification {
+    pub kind: NotificationKind,
+    pub title: String,
+    pub message: String,
+    #[serde(serialize_with = "serialize_urgency")]
+    pub urgency: Urgency,
+}
+
+fn serialize_urgency<S: serde::Serializer>(urgency: &Urgency, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(urgency.as_str())
+}
+
+impl Notification {
+    pub fn new(kind: NotificationKind, title: &str, message: &str) -> Self {
+        let urgency = match kind {
+            NotificationKind::BreakReminder | NotificationKind::MemoryDue => Urgency::Critical,
+            NotificationKind::BreakOver | NotificationKind::Info => Urgency::Normal,
+        };
+        Self {
+            kind,
+            title: title.to_string(),
+            message: message.to_string(),
+            urgency,
+        }
+    }
+
+    pub fn with_urgency(mut self, urgency: Urgency) -> Self {
+        self.urgency = urgency;
+        self
+    }
+}
+
+/// A place a notification can be delivered to.
+///
+/// Implementations must not panic on delivery failure; they report the
+/// problem on stderr and let the remaining sinks carry on.
+pub trait Notifier: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn notify(&self, notification: &Notification);
+}
+
+/// The sinks selectable with `--notify`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SinkKind {
+    /// Print the notification in the terminal
+    Terminal,
+    /// Native desktop notification (and dialog on macOS)
+    Desktop,
+    /// Ring the terminal bell
+    Bell,
+    /// Run the command given with --notify-command
+    Command,
+    /// POST JSON to the URL given with --webhook-url
+    Webhook,
+}
+
+pub struct TerminalNotifier;
+
+impl Notifier for TerminalNotifier {
+    fn name(&self) -> &'static str {
+        "terminal"
+    }
+
+    fn notify(&self, notification: &Notification) {
+        let line = format!("{} - {}", notification.title, notification.message);
+        match notification.kind {
+            NotificationKind::BreakReminder => println!("\n{}", line.bright_yellow().bold()),
+            NotificationKind::BreakOver => println!("\n{}", line.bright_green().bold()),
+            NotificationKind::MemoryDue => println!("\n{}", line.bright_magenta().bold()),
+            NotificationKind::Info => println!("\n{}", line.bright_cyan()),
+        }
+    }
+}
+
+pub struct DesktopNotifier {
+    /// Candidate icon files; the first one that exists is used.
+    pub icon_paths: Vec<String>,
+}
+
+impl Notifier for DesktopNotifier {
+    fn name(&self) -> &'static str {
+        "desktop"
+    }
+
+    fn notify(&self, notification: &Notification) {
+        let icon = resolve_coffee_icon(&self.icon_paths);
+        show_system_notification(&notification.title, &notification.message, notification.urgency, icon.as_deref());
+
+        // Show modal dialog popup in IDE/terminal
+        if notification.kind == NotificationKind::BreakReminder {
+            show_coffee_dialog(icon.as_deref());
+        }
+    }
+}
+
+pub struct BellNotifier;
+
+impl Notifier for BellNotifier {
+    fn name(&self) -> &'static str {
+        "bell"
+    }
+
+    fn notify(&self, _notification: &Notification) {
+        print!("\x07");
+        let _ = io::stdout().flush();
+    }
+}
+
+/// Runs a shell hook with the notification exposed as environment variables
+/// (`COFFEE_BREAK_KIND`, `COFFEE_BREAK_TITLE`, `COFFEE_BREAK_MESSAGE`,
+/// `COFFEE_BREAK_URGENCY`).
+pub struct CommandNotifier {
+    pub command: String,
+}
+
+impl Notifier for CommandNotifier {
+    fn name(&self) -> &'static str {
+        "command"
+    }
+
+    fn notify(&self, notification: &Notification) {
+        let result = Command::new("bash")
+            .arg("-c")
+            .arg(&self.command)
+            .env("COFFEE_BREAK_KIND", notification.kind.as_str())
+            .env("COFFEE_BREAK_TITLE", &notification.title)
+            .env("COFFEE_BREAK_MESSAGE", &notification.message)
+            .env("COFFEE_BREAK_URGENCY", notification.urgency.as_str())
+            .output();
+
+        match result {
+            Ok(output) => {
+                if !output.status.success() {
+                    eprintln!(
+                        "[coffee reminder] notify command failed: {}",
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    );
+                }
+            }
+            Err(err) => {
+                eprintln!("[coffee reminder] could not run notify command: {}", err);
+            }
+        }
+    }
+}
+
+/// POSTs the notification as JSON to a webhook URL.
+pub struct WebhookNotifier {
+    pub url: String,
+}
+
+impl Notifier for WebhookNotifier {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    fn notify(&self, notification: &Notification) {
+        let body = serde_json::to_string(notification).unwrap();
+        let result = ureq::post(&self.url)
+            .timeout(std::time::Duration::from_secs(10))
+            .set("Content-Type", "application/json")
+            .send_string(&body);
+
+        if let Err(err) = result {
+            eprintln!("[coffee reminder] webhook {} failed: {}", self.url, err);
+        }
+    }
+}
+
+/// Fans a notification out to every configured sink.
+#[derive(Default)]
+pub struct Notifiers {
+    sinks: Vec<Box<dyn Notifier>>,
+}
+
+impl Notifiers {
+    /// Build the sink list from `--notify` selections. `command` and `webhook`
+    /// need their parameter; passing a parameter alone also enables its sink.
+    pub fn from_selection(
+        kinds: &[SinkKind],
+        command: Option<String>,
+        webhook_url: Option<String>,
+        icon_paths: &[String],
+    ) -> Result<Self, String> {
+        let mut kinds = kinds.to_vec();
+        if command.is_some() && !kinds.contains(&SinkKind::Command) {
+            kinds.push(SinkKind::Command);
+        }
+        if webhook_url.is_some() && !kinds.contains(&SinkKind::Webhook) {
+            kinds.push(SinkKind::Webhook);
+        }
+
+        let mut notifiers = Self::default();
+        for kind in kinds {
+            if notifiers.sinks.iter().any(|s| s.name() == kind_name(kind)) {
+                continue;
+            }
+            match kind {
+                SinkKind::Terminal => notifiers.add(TerminalNotifier),
+                SinkKind::Desktop => notifiers.add(DesktopNotifier {
+                    icon_paths: icon_paths.to_vec(),
+                }),
+                SinkKind::Bell => notifiers.add(BellNotifier),
+                SinkKind::Command => {
+                    let command = command
+                        .clone()
+                        .ok_or("the 'command' sink needs --notify-command <cmd>")?;
+                    notifiers.add(CommandNotifier { command });
+                }
+                SinkKind::Webhook => {
+                    let url = webhook_url
+                        .clone()
+                        .ok_or("the 'webhook' sink needs --webhook-url <url>")?;
+                    notifiers.add(WebhookNotifier { url });
+                }
+            }
+        }
+        Ok(notifiers)
+    }
+
+    pub fn add<N: Notifier + 'static>(&mut self, notifier: N) {
+        self.sinks.push(Box::new(notifier));
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.sinks.iter().map(|s| s.name()).collect()
+    }
+
+    pub fn notify(&self, notification: &Notification) {
+        for sink in &self.sinks {
+            sink.notify(notification);
+        }
+    }
+}
+
+fn kind_name(kind: SinkKind) -> &'static str {
+    match kind {
+        SinkKind::Terminal => "terminal",
+        SinkKind::Desktop => "desktop",
+        SinkKind::Bell => "bell",
+        SinkKind::Command => "command",
+        SinkKind::Webhook => "webhook",
+    }
+}
+
+fn resolve_coffee_icon(image_paths: &[String]) -> Option<String> {
+    for path in image_paths {
+        let candidate = Path::new(path);
+        if candidate.exists() {
+            if let Ok(abs) = std::fs::canonicalize(candidate) {
+                return Some(abs.to_string_lossy().to_string());
+            } else {
+                return Some(candidate.to_string_lossy().to_string());
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(target_os = "macos")]
+fn run_osascript(script: &str, context: &str) {
+    match Command::new("osascript").arg("-e").arg(script).output() {
+        Ok(output) => {
+            if !output.status.success() {
+                eprintln!(
+                    "[coffee reminder] {} failed: {}",
+                    context,
+                    String::from_utf8_lossy(&output.stderr)
+                );
+            }
+        }
+        Err(err) => {
+            eprintln!("[coffee reminder] could not run osascript for {}: {}", context, err);
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn run_powershell(script: &str, context: &str) {
+    match Command::new("powershell")
+        .arg("-NoLogo")
+        .arg("-NoProfile")
+        .arg("-Command")
+        .arg(script)
+        .output()
+    {
+        Ok(output) => {
+            if !output.status.success() {
+                eprintln!(
+                    "[coffee reminder] {} failed: {}",
+                    context,
+                    String::from_utf8_lossy(&output.stderr)
+                );
+            }
+        }
+        Err(err) => {
+            eprintln!(
+                "[coffee reminder] could not run PowerShell for {}: {}",
+                context, err
+            );
+        }
+    }
+}
+
+/// How long a desktop notification stays on screen before the server may hide it.
+#[cfg(target_os = "linux")]
+const NOTIFICATION_TIMEOUT_MS: i32 = 10_000;
+
+/// Urgency levels defined by the freedesktop notification spec.
+/// Only the Linux desktop backend can express them; other platforms ignore them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Urgency {
+    Low,
+    #[default]
+    Normal,
+    Critical,
+}
+
+impl Urgency {
+    #[cfg(target_os = "linux")]
+    fn as_byte(self) -> u8 {
+        match self {
+            Urgency::Low => 0,
+            Urgency::Normal => 1,
+            Urgency::Critical => 2,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Urgency::Low => "low",
+            Urgency::Normal => "normal",
+            Urgency::Critical => "critical",
+        }
+    }
+}
+
+/// Quote a value as a GVariant string literal for `gdbus call`.
+#[cfg(target_os = "linux")]
+fn gvariant_string(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+/// Call `org.freedesktop.Notifications.Notify` on the session bus.
+///
+/// `COFFEE_BREAK_DBUS_ADDRESS` points the call at a different bus (e.g. a
+/// private `dbus-daemon` standing in for the desktop session).
+#[cfg(target_os = "linux")]
+fn notify_via_dbus(title: &str, message: &str, icon: &str, urgency: Urgency, timeout_ms: i32) -> bool {
+    let mut cmd = Command::new("gdbus");
+    cmd.arg("call");
+    match std::env::var("COFFEE_BREAK_DBUS_ADDRESS") {
+        Ok(address) if !address.is_empty() => {
+            cmd.arg("--address").arg(address);
+        }
+        _ => {
+            cmd.arg("--session");
+        }
+    }
+    cmd.arg("--dest")
+        .arg("org.freedesktop.Notifications")
+        .arg("--object-path")
+        .arg("/org/freedesktop/Notifications")
+        .arg("--method")
+        .arg("org.freedesktop.Notifications.Notify")
+        .arg(gvariant_string("coffee-break"))
+        .arg("0")
+        .arg(gvariant_string(icon))
+        .arg(gvariant_string(title))
+        .arg(gvariant_string(message))
+        .arg("@as []")
+        .arg(format!("{{'urgency': <byte {}>}}", urgency.as_byte()))
+        .arg(timeout_ms.to_string());
+
+    match cmd.output() {
+        Ok(output) if output.status.success() => true,
+        Ok(output) => {
+            eprintln!(
+                "[coffee reminder] D-Bus notification failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+            false
+        }
+        Err(_) => false,
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn notify_via_notify_send(title: &str, message: &str, icon: &str, urgency: Urgency, timeout_ms: i32) -> bool {
+    match Command::new("notify-send")
+        .arg("--app-name=coffee-break")
+        .arg(format!("--urgency={}", urgency.as_str()))
+        .arg(format!("--expire-time={}", timeout_ms))
+        .arg(format!("--icon={}", icon))
+        .arg(title)
+        .arg(message)
+        .output()
+    {
+        Ok(output) => {
+            if !output.status.success() {
+                eprintln!(
+                    "[coffee reminder] notify-send failed: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+            }
+            output.status.success()
+        }
+        Err(_) => false,
+    }
+}
+
+/// Deliver a desktop notification over D-Bus, falling back to `notify-send`.
+/// Returns `false` when neither backend could show it.
+#[cfg(target_os = "linux")]
+fn send_linux_notification(title: &str, message: &str, icon: &str, urgency: Urgency, timeout_ms: i32) -> bool {
+    notify_via_dbus(title, message, icon, urgency, timeout_ms)
+        || notify_via_notify_send(title, message, icon, urgency, timeout_ms)
+}
+
+fn show_system_notification(title: &str, message: &str, urgency: Urgency, icon: Option<&str>) {
+    #[cfg(not(target_os = "linux"))]
+    let _ = urgency;
+
+    #[cfg(target_os = "macos")]
+    {
+        let icon_clause = icon
+            .map(|p| format!("with icon POSIX file \"{}\"", p))
+            .unwrap_or_else(|| "with icon note".to_string());
+
+        let script = format!(
+            "display notification \"{}\" with title \"{}\" sound name \"Glass\" {}",
+            message, title, icon_clause
+        );
+
+        run_osascript(&script, "notification");
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        fn escape_ps(value: &str) -> String {
+            value.replace('"', "`\"")
+        }
+
+        let icon_snippet = icon.map(|path| {
+            let url = format!(
+                "file:///{}",
+                path.replace('\\', "/").replace(' ', "%20")
+            );
+            format!(
+                "$xml.GetElementsByTagName(\"image\")[0].Attributes.GetNamedItem(\"src\").Value = \"{}\";",
+                url
+            )
+        }).unwrap_or_default();
+
+        let script = format!(
+            r#"
+Add-Type -AssemblyName System.Runtime.WindowsRuntime | Out-Null
+[Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime] | Out-Null
+$template = [Windows.UI.Notifications.ToastTemplateType]::ToastImageAndText02
+$xml = [Windows.UI.Notifications.ToastNotificationManager]::GetTemplateContent($template)
+$xml.GetElementsByTagName("text")[0].AppendChild($xml.CreateTextNode("{title}")) | Out-Null
+$xml.GetElementsByTagName("text")[1].AppendChild($xml.CreateTextNode("{message}")) | Out-Null
+{icon}
+$toast = [Windows.UI.Notifications.ToastNotification]::new($xml)
+$notifier = [Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier("coffee-break")
+$notifier.Show($toast)
+"#,
+            title = escape_ps(title),
+            message = escape_ps(message),
+            icon = icon_snippet
+        );
+
+        run_powershell(&script, "notification");
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let icon = icon.unwrap_or("dialog-information");
+        if !send_linux_notification(title, message, icon, urgency, NOTIFICATION_TIMEOUT_MS) {
+            println!("☕ {} - {}", title, message);
+        }
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    {
+        println!("☕ {} - {}", title, message);
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn show_coffee_dialog(icon: Option<&str>) {
+    // Show a compact modal dialog box with coffee theme
+    // This requires user interaction (clicking OK)
+
+    let icon_clause = icon
+        .map(|p| format!("with icon POSIX file \"{}\"", p))
+        .unwrap_or_else(|| "with icon note".to_string());
+
+    let script = format!(
+        r#"
+        tell application "System Events"
+            activate
+        end tell
+        
+        display dialog "☕ COFFEE BREAK TIME! ☕
+
+You've been coding for a while!
+Time to take a break and grab some coffee! 🍵
+
+Your code will still be here when you return!" with title "☕ Coffee Break" buttons {{"☕ OK ☕"}} default button 1 {} giving up after 30
+        "#,
+        icon_clause
+    );
+    
+    run_osascript(&script, "dialog");
+}
+
+#[cfg(not(target_os = "macos"))]
+fn show_coffee_dialog(_icon: Option<&str>) {
+    // Dialog not available on this platform; notification already shown
+}
+
@@ -0,0 +1,207 @@
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+impl Phase {
+    pub fn label(self) -> &'static str {
+        match self {
+            Phase::Work => "Work",
+            Phase::ShortBreak => "Short break",
+            Phase::LongBreak => "Long break",
+        }
+    }
+
+    pub fn is_break(self) -> bool {
+        self != Phase::Work
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum BreakPlan {
+    /// One reminder every `interval`; the break itself is not tracked.
+    Simple { interval: Duration },
+    /// Work blocks separated by short breaks, with a long break every
+    /// `long_break_every` completed work blocks.
+    Pomodoro {
+        work: Duration,
+        short_break: Duration,
+        long_break: Duration,
+        long_break_every: u32,
+    },
+}
+
+/// What happened when the timer crossed a phase boundary.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Transition {
+    /// Simple mode: the interval elapsed.
+    Reminder,
+    /// Pomodoro mode: a work block ended and this break starts.
+    BreakStarted(Phase),
+    /// Pomodoro mode: the break ended and a new work block starts.
+    BreakOver,
+}
+
+/// Pomodoro state machine. Time only moves when [`TimerState::advance`] is
+/// called, so the scheduler decides which wall-clock time counts.
+#[derive(Clone, Debug)]
+pub struct TimerState {
+    pub plan: BreakPlan,
+    pub phase: Phase,
+    /// Completed work blocks (or fired reminders in simple mode).
+    pub cycle: u32,
+    /// Time spent in the current phase.
+    pub elapsed: Duration,
+    /// While paused, [`TimerState::advance`] does not move the clock.
+    pub paused: bool,
+    /// Replaces the planned length of the current phase (set by snooze).
+    pub length_override: Option<Duration>,
+}
+
+impl TimerState {
+    pub fn new(plan: BreakPlan) -> Self {
+        Self {
+            plan,
+            phase: Phase::Work,
+            cycle: 0,
+            elapsed: Duration::ZERO,
+            paused: false,
+            length_override: None,
+        }
+    }
+
+    pub fn phase_length(&self) -> Duration {
+        if let Some(length) = self.length_override {
+            return length;
+        }
+        match (&self.plan, self.phase) {
+            (BreakPlan::Simple { interval }, _) => *interval,
+            (BreakPlan::Pomodoro { work, .. }, Phase::Work) => *work,
+            (BreakPlan::Pomodoro { short_break, .. }, Phase::ShortBreak) => *short_break,
+            (BreakPlan::Pomodoro { long_break, .. }, Phase::LongBreak) => *long_break,
+        }
+    }
+
+    pub fn remaining(&self) -> Duration {
+        self.phase_length().saturating_sub(self.elapsed)
+    }
+
+    /// Position inside the current long-break cycle, 1-based
+    /// (e.g. work block 2 of 4). `None` in simple mode.
+    pub fn cycle_position(&self) -> Option<(u32, u32)> {
+        match &self.plan {
+            BreakPlan::Simple { .. } => None,
+            BreakPlan::Pomodoro { long_break_every, .. } => {
+                let every = (*long_break_every).max(1);
+                let done_in_set = self.cycle % every;
+                let position = if self.phase == Phase::Work {
+                    done_in_set + 1
+                } else if done_in_set == 0 {
+                    every
+                } else {
+                    done_in_set
+                };
+                Some((position, every))
+            }
+        }
+    }
+
+    pub fn advance(&mut self, dt: Duration) -> Option<Transition> {
+        if self.paused {
+            return None;
+        }
+        self.elapsed += dt;
+        if self.elapsed < self.phase_length() {
+            return None;
+        }
+        Some(self.finish_phase())
+    }
+
+    /// End the current phase immediately and move to the next one.
+    pub fn finish_phase(&mut self) -> Transition {
+        self.elapsed = Duration::ZERO;
+        self.length_override = None;
+        match (&self.plan, self.phase) {
+            (BreakPlan::Simple { .. }, _) => {
+                self.cycle += 1;
+                Transition::Reminder
+            }
+            (BreakPlan::Pomodoro { long_break_every, .. }, Phase::Work) => {
+                self.cycle += 1;
+                let every = (*long_break_every).max(1);
+                self.phase = if self.cycle.is_multiple_of(every) {
+                    Phase::LongBreak
+                } else {
+                    Phase::ShortBreak
+                };
+                Transition::BreakStarted(self.phase)
+            }
+            (BreakPlan::Pomodoro { .. }, _) => {
+                self.phase = Phase::Work;
+                Transition::BreakOver
+            }
+        }
+    }
+
+    /// Remind again in `delay`. During a Pomodoro break this postpones the
+    /// break: the clock goes back to work and the same break follows.
+    pub fn snooze(&mut self, delay: Duration) {
+        if self.phase.is_break() {
+            self.phase = Phase::Work;
+            self.cycle = self.cycle.saturating_sub(1);
+            self.elapsed = Duration::ZERO;
+        }
+        self.length_override = Some(self.elapsed + delay);
+    }
+
+    /// Skip the upcoming break (while working) or cut the current break short.
+    pub fn skip(&mut self) -> Option<Transition> {
+        if self.phase.is_break() {
+            return Some(self.finish_phase());
+        }
+        if matches!(self.plan, BreakPlan::Pomodoro { .. }) {
+            self.cycle += 1;
+        }
+        self.elapsed = Duration::ZERO;
+        self.length_override = None;
+        None
+    }
+
+    /// The user stepped away long enough to count as a break: restart the
+    /// current work block without touching the cycle count.
+    pub fn natural_break(&mut self) {
+        if self.phase == Phase::Work {
+            self.elapsed = Duration::ZERO;
+            self.length_override = None;
+        }
+    }
+
+    /// Start over from the first work block, keeping the plan and pause state.
+    pub fn reset(&mut self) {
+        let paused = self.paused;
+        *self = Self::new(self.plan.clone());
+        self.paused = paused;
+    }
+}
+
+pub fn format_minutes(d: Duration) -> String {
+    let minutes = d.as_secs() / 60;
+    if minutes == 1 {
+        "1 minute".to_string()
+    } else {
+        format!("{} minutes", minutes)
+    }
+}
+
+pub fn format_clock(d: Duration) -> String {
+    let secs = d.as_secs();
+    if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, (secs % 3600) / 60, secs % 60)
+    } else {
+        format!("{:02}:{:02}", secs / 60, secs % 60)
+    }
+}
@@ -0,0 +1,104 @@
+use chrono::{DateTime, Duration as ChronoDuration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use colored::*;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::memory::{print_entry, Memory, MemoryEntry};
+use crate::notifier::{Notification, NotificationKind, Notifiers};
+
+/// How often the reminder thread looks for due memories.
+const CHECK_EVERY: Duration = Duration::from_secs(5);
+
+/// A reminder more than this late was missed (e.g. while coffee-break was closed).
+const MISSED_AFTER: ChronoDuration = ChronoDuration::minutes(1);
+
+/// `--at` value: `15:00` (today, or tomorrow if that's passed) or
+/// `2024-05-01T15:00`.
+pub fn parse_at(value: &str) -> Result<DateTime<Local>, String> {
+    let invalid = || format!("invalid --at value '{}' (try 15:00 or 2024-05-01T15:00)", value);
+    let local = |naive: NaiveDateTime| Local.from_local_datetime(&naive).earliest().ok_or_else(invalid);
+
+    if let Ok(naive) = NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M") {
+        return local(naive);
+    }
+    let time = NaiveTime::parse_from_str(value, "%H:%M").map_err(|_| invalid())?;
+    let today: NaiveDate = Local::now().date_naive();
+    let at = local(today.and_time(time))?;
+    if at <= Local::now() {
+        local((today + ChronoDuration::days(1)).and_time(time))
+    } else {
+        Ok(at)
+    }
+}
+
+/// `--in` value: an amount with a unit, optionally combined, e.g. `45m`,
+/// `2h`, `1h30m` or `3d`.
+pub fn parse_in(value: &str) -> Result<DateTime<Local>, String> {
+    let invalid = || format!("invalid --in value '{}' (try 45m, 2h, 1h30m or 3d)", value);
+    let mut total = ChronoDuration::zero();
+    let mut amount = String::new();
+    for c in value.chars() {
+        if c.is_ascii_digit() {
+            amount.push(c);
+            continue;
+        }
+        let n: i64 = amount.parse().map_err(|_| invalid())?;
+        total += match c {
+            'm' => ChronoDuration::minutes(n),
+            'h' => ChronoDuration::hours(n),
+            'd' => ChronoDuration::days(n),
+            _ => return Err(invalid()),
+        };
+        amount.clear();
+    }
+    if !amount.is_empty() || total.is_zero() {
+        return Err(invalid());
+    }
+    Ok(Local::now() + total)
+}
+
+/// Deliver memory reminders as they come due. Reminders that came due while
+/// coffee-break wasn't running fire on the first check, right at startup.
+pub fn start_reminders(memory: Arc<Mutex<Memory>>, notifiers: Arc<Notifiers>) {
+    thread::spawn(move || loop {
+        let now = Local::now();
+        let due = memory.lock().unwrap().claim_due(now);
+        match due {
+            Ok(entries) => {
+                for entry in entries {
+                    announce_due(&entry, now, &notifiers);
+                }
+            }
+            Err(err) => println!("\n{}", format!("❌ {}", err).bright_red()),
+        }
+        thread::sleep(CHECK_EVERY);
+    });
+}
+
+fn announce_due(entry: &MemoryEntry, now: DateTime<Local>, notifiers: &Notifiers) {
+    let mut message = entry.text.clone();
+    if let Some(due) = entry.due_local().filter(|due| now - *due > MISSED_AFTER) {
+        message.push_str(&format!(" (missed, was due {})", due.format("%Y-%m-%d %H:%M")));
+    }
+    if let Some(project) = entry.project_name() {
+        message.push_str(&format!(" [{}]", project));
+    }
+    notifiers.notify(&Notification::new(
+        NotificationKind::MemoryDue,
+        &format!("⏰ Reminder #{}", entry.id),
+        &message,
+    ));
+}
+
+pub fn print_due(mem: &Memory) {
+    let entries = mem.with_due();
+    if entries.is_empty() {
+        println!("⏰ No reminders. Add one with remember --at 15:00 <text> or remember --in 45m <text>");
+        return;
+    }
+    println!("⏰ Reminders (all projects, soonest first):");
+    for entry in entries {
+        print_entry(entry);
+    }
+}
@@ -0,0 +1,315 @@
+use chrono::{DateTime, Duration as ChronoDuration, Local, NaiveDate, TimeZone};
+use colored::*;
+
+use crate::memory::{print_entry_with_text, Memory, MemoryEntry};
+
+/// Filters shared by the memory listing commands.
+#[derive(Debug, Default)]
+pub struct MemoryFilter {
+    pub tags: Vec<String>,
+    pub since: Option<DateTime<Local>>,
+    pub project: Option<String>,
+    /// `Some(true)` = only done, `Some(false)` = only open.
+    pub done: Option<bool>,
+    /// Only this project's notes and global notes; `None` searches everything.
+    pub scope: Option<String>,
+}
+
+impl MemoryFilter {
+    pub fn matches(&self, entry: &MemoryEntry) -> bool {
+        if !self.tags.iter().all(|t| entry.tags.contains(t)) {
+            return false;
+        }
+        if let Some(since) = self.since {
+            match entry.created_local() {
+                Some(created) if created >= since => {}
+                _ => return false,
+            }
+        }
+        if let Some(project) = &self.project {
+            let project = project.to_lowercase();
+            match &entry.project {
+                Some(p) if p.to_lowercase().contains(&project) => {}
+                _ => return false,
+            }
+        }
+        if let Some(done) = self.done {
+            if entry.done != done {
+                return false;
+            }
+        }
+        if let Some(scope) = &self.scope {
+            if !entry.in_scope(scope) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// A parsed `mem search` command line.
+#[derive(Debug, Default)]
+pub struct SearchQuery {
+    pub terms: Vec<String>,
+    pub filter: MemoryFilter,
+}
+
+/// Parse `<query> [--tag t] [--since when] [--project p] [--done|--open] [--all]`.
+/// Without `--all` (or `--project`) the search is limited to `scope`.
+pub fn parse_search(args: &str, scope: &str) -> Result<SearchQuery, String> {
+    let mut query = SearchQuery::default();
+    query.filter.scope = Some(scope.to_string());
+    let mut words = args.split_whitespace();
+    while let Some(word) = words.next() {
+        match word {
+            "--tag" => {
+                let tag = words.next().ok_or("--tag needs a value")?;
+                query.filter.tags.push(tag.trim_start_matches('#').to_lowercase());
+            }
+            "--since" => {
+                let when = words.next().ok_or("--since needs a value")?;
+                query.filter.since = Some(parse_since(when)?);
+            }
+            "--project" => {
+                let project = words.next().ok_or("--project needs a value")?;
+                query.filter.project = Some(project.to_string());
+                query.filter.scope = None;
+            }
+            "--all" => query.filter.scope = None,
+            "--done" => query.filter.done = Some(true),
+            "--open" => query.filter.done = Some(false),
+            flag if flag.starts_with("--") => return Err(format!("unknown option {}", flag)),
+            term => query.terms.push(term.to_lowercase()),
+        }
+    }
+    Ok(query)
+}
+
+/// Accepts `YYYY-MM-DD`, `today`, `yesterday` or a relative age like
+/// `30m`, `12h`, `3d` or `2w`.
+pub fn parse_since(value: &str) -> Result<DateTime<Local>, String> {
+    let invalid = || format!("invalid value '{}' (try 2024-05-01, 3d, 2w, 12h or 30m)", value);
+    let start_of = |day: NaiveDate| {
+        Local
+            .from_local_datetime(&day.and_hms_opt(0, 0, 0).unwrap())
+            .earliest()
+            .ok_or_else(invalid)
+    };
+    let today = Local::now().date_naive();
+
+    match value {
+        "today" => return start_of(today),
+        "yesterday" => return start_of(today - ChronoDuration::days(1)),
+        _ => {}
+    }
+    if let Ok(day) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return start_of(day);
+    }
+
+    let (amount, unit) = value.split_at(value.len().saturating_sub(1));
+    let amount: i64 = amount.parse().map_err(|_| invalid())?;
+    let age = match unit {
+        "m" => ChronoDuration::minutes(amount),
+        "h" => ChronoDuration::hours(amount),
+        "d" => ChronoDuration::days(amount),
+        "w" => ChronoDuration::weeks(amount),
+        _ => return Err(invalid()),
+    };
+    Ok(Local::now() - age)
+}
+
+/// A ranked search hit with the char ranges of `text` that matched.
+pub struct SearchHit<'a> {
+    pub entry: &'a MemoryEntry,
+    pub score: f64,
+    pub highlights: Vec<(usize, usize)>,
+}
+
+pub fn search<'a>(mem: &'a Memory, query: &SearchQuery) -> Vec<SearchHit<'a>> {
+    let mut hits: Vec<SearchHit> = mem
+        .entries
+        .iter()
+        .filter(|e| query.filter.matches(e))
+        .filter_map(|entry| {
+            let mut score = 0.0;
+            let mut highlights = Vec::new();
+            for term in &query.terms {
+                let (term_score, ranges) = score_term(term, entry)?;
+                score += term_score;
+                highlights.extend(ranges);
+            }
+            Some(SearchHit {
+                entry,
+                score,
+                highlights,
+            })
+        })
+        .collect();
+
+    hits.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(b.entry.id.cmp(&a.entry.id))
+    });
+    hits
+}
+
+/// Best match of one query term against an entry's text and tags. Exact
+/// words beat prefixes, which beat substrings, typos and finally loose
+/// in-order character matches. Returns `None` if the term doesn't match.
+fn score_term(term: &str, entry: &MemoryEntry) -> Option<(f64, Vec<(usize, usize)>)> {
+    let text: Vec<char> = entry
+        .text
+        .chars()
+        .map(|c| c.to_lowercase().next().unwrap_or(c))
+        .collect();
+    let needle: Vec<char> = term.chars().collect();
+
+    let tag_score = entry
+        .tags
+        .iter()
+        .map(|tag| {
+            if tag == term {
+                3.0
+            } else if tag.starts_with(term) {
+                2.0
+            } else {
+                0.0
+            }
+        })
+        .fold(0.0, f64::max);
+
+    let mut best: Option<(f64, Vec<(usize, usize)>)> = None;
+    let mut consider = |score: f64, ranges: Vec<(usize, usize)>| {
+        if best.as_ref().is_none_or(|(s, _)| score > *s) {
+            best = Some((score, ranges));
+        }
+    };
+
+    // Substring matches, scored by how well they line up with word boundaries.
+    for start in find_all(&text, &needle) {
+        let end = start + needle.len();
+        let word_start = start == 0 || !text[start - 1].is_alphanumeric();
+        let word_end = end == text.len() || !text[end].is_alphanumeric();
+        let score = match (word_start, word_end) {
+            (true, true) => 3.0,
+            (true, false) => 2.0,
+            _ => 1.5,
+        };
+        consider(score, vec![(start, end)]);
+    }
+
+    // Typos: a word within a small edit distance.
+    if needle.len() >= 3 {
+        let max_distance = if needle.len() >= 8 { 2 } else { 1 };
+        for (start, end) in words(&text) {
+            let distance = levenshtein(&needle, &text[start..end]);
+            if distance <= max_distance {
+                consider(1.0 - 0.25 * distance as f64, vec![(start, end)]);
+            }
+        }
+
+        // Characters in order, e.g. "prsr" in "parser".
+        if let Some(positions) = subsequence(&text, &needle) {
+            let span = positions.last().unwrap() - positions[0] + 1;
+            let score = 0.5 * needle.len() as f64 / span as f64;
+            consider(score, positions.iter().map(|&p| (p, p + 1)).collect());
+        }
+    }
+
+    match best {
+        Some((score, ranges)) if score >= tag_score => Some((score, ranges)),
+        _ if tag_score > 0.0 => Some((tag_score, vec![])),
+        _ => None,
+    }
+}
+
+fn find_all(haystack: &[char], needle: &[char]) -> Vec<usize> {
+    if needle.is_empty() || needle.len() > haystack.len() {
+        return vec![];
+    }
+    (0..=haystack.len() - needle.len())
+        .filter(|&i| haystack[i..i + needle.len()] == *needle)
+        .collect()
+}
+
+/// Char ranges of the alphanumeric words in `text`.
+fn words(text: &[char]) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    let mut start = None;
+    for (i, c) in text.iter().enumerate() {
+        match (c.is_alphanumeric(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                ranges.push((s, i));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        ranges.push((s, text.len()));
+    }
+    ranges
+}
+
+fn subsequence(text: &[char], needle: &[char]) -> Option<Vec<usize>> {
+    let mut positions = Vec::with_capacity(needle.len());
+    let mut from = 0;
+    for c in needle {
+        let offset = text[from..].iter().position(|t| t == c)?;
+        positions.push(from + offset);
+        from += offset + 1;
+    }
+    Some(positions)
+}
+
+fn levenshtein(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != cb);
+            current[j + 1] = (previous[j] + cost)
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Render `text` with the given char ranges highlighted.
+pub fn highlight(text: &str, ranges: &[(usize, usize)]) -> String {
+    let mut out = String::new();
+    for (i, c) in text.chars().enumerate() {
+        let s = c.to_string();
+        if ranges.iter().any(|&(start, end)| i >= start && i < end) {
+            out.push_str(&s.black().on_bright_yellow().bold().to_string());
+        } else {
+            out.push_str(&s.bright_yellow().to_string());
+        }
+    }
+    out
+}
+
+pub fn print_search_results(query: &SearchQuery, hits: &[SearchHit]) {
+    let terms = if query.terms.is_empty() {
+        "the filters".to_string()
+    } else {
+        format!("'{}'", query.terms.join(" "))
+    };
+    if hits.is_empty() {
+        println!("🔎 No memories match {}", terms.bright_white());
+        return;
+    }
+    println!(
+        "🔎 {} match(es) for {}:",
+        hits.len().to_string().bright_green(),
+        terms.bright_white()
+    );
+    for hit in hits {
+        print_entry_with_text(hit.entry, &highlight(&hit.entry.text, &hit.highlights));
+    }
+}
@@ -1,10 +1,23 @@
 use colored::*;
-use std::process::Command;
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
 
-pub fn run_smart_shell(cmd: &str) {
+/// The first configured dangerous command that `cmd` invokes, if any.
+fn needs_confirmation<'a>(cmd: &str, confirm_commands: &'a [String]) -> Option<&'a String> {
+    confirm_commands.iter().find(|c| {
+        cmd == c.as_str() || cmd.starts_with(&format!("{} ", c)) || cmd.contains(&format!(" {} ", c))
+    })
+}
+
+pub fn run_smart_shell(cmd: &str, confirm_commands: &[String]) {
     // Safety layer: warn before dangerous commands
-    if cmd.starts_with("rm ") || cmd.contains(" rm ") {
-        println!("{}", "⚠️  You are about to delete files. Proceed? (y/n)".red());
+    if let Some(dangerous) = needs_confirmation(cmd, confirm_commands) {
+        let warning = if dangerous == "rm" {
+            "⚠️  You are about to delete files. Proceed? (y/n)".to_string()
+        } else {
+            format!("⚠️  You are about to run '{}'. Proceed? (y/n)", dangerous)
+        };
+        println!("{}", warning.red());
         let mut answer = String::new();
         std::io::stdin().read_line(&mut answer).unwrap();
         if answer.trim() != "y" {
@@ -13,9 +26,7 @@ pub fn run_smart_shell(cmd: &str) {
         }
     }
 
-    let output = Command::new("bash")
-        .arg("-c")
-        .arg(cmd)
+    let output = shell_command(cmd)
         .output()
         .expect("failed to execute shell");
 
@@ -26,3 +37,41 @@ pub fn run_smart_shell(cmd: &str) {
 }
 
 
+
+fn shell_command(cmd: &str) -> Command {
+    let mut command = Command::new("bash");
+    command.arg("-c").arg(cmd);
+    command
+}
+
+/// Start `cmd` in `dir` without a terminal to ask for confirmation, so
+/// commands that would need one are refused. Output is piped.
+pub fn spawn_unattended(cmd: &str, dir: &Path, confirm_commands: &[String]) -> Result<Child, String> {
+    if let Some(dangerous) = needs_confirmation(cmd, confirm_commands) {
+        return Err(format!("'{}' needs confirmation, so it can't run automatically", dangerous));
+    }
+    let mut command = shell_command(cmd);
+    command
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    // Own process group, so `stop` also reaches whatever the shell started.
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+    command
+        .spawn()
+        .map_err(|e| format!("failed to start '{}': {}", cmd, e))
+}
+
+/// Stop a command started by `spawn_unattended`, including its children.
+pub fn stop(child: &mut Child) {
+    #[cfg(unix)]
+    let _ = Command::new("kill")
+        .arg("-TERM")
+        .arg(format!("-{}", child.id()))
+        .stderr(Stdio::null())
+        .status();
+    let _ = child.kill();
+    let _ = child.wait();
+}
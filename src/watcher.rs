@@ -1,26 +1,290 @@
 use colored::*;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use notify::event::{EventKind, ModifyKind, RenameMode};
 use notify::{Config, RecommendedWatcher, RecursiveMode, Watcher};
-use std::{path::Path, sync::mpsc::channel, time::Duration};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, RecvTimeoutError};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::actions::{ActionRunner, WatchAction};
+use crate::activity::Activity;
+use crate::changes::ChangeLog;
+
+/// A batch is flushed after this many debounce periods even if events keep
+/// coming, so a long build still produces the occasional summary.
+const MAX_BATCH_WINDOWS: u32 = 10;
+
+/// How the watcher polls, filters and batches events.
+pub struct WatchOptions {
+    pub poll_interval: Duration,
+    /// Quiet period after the last event before a batch is reported.
+    pub debounce: Duration,
+    /// Globs (relative to the watched folder) that are never reported.
+    pub ignore: Vec<String>,
+    /// Commands to run when matching files change.
+    pub actions: Vec<WatchAction>,
+    /// Actions invoking these are refused, as there's nobody to confirm them.
+    pub confirm_commands: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Modified,
+    Created,
+    Removed,
+    Renamed,
+}
+
+impl ChangeKind {
+    /// The change behind a notify event; `None` for reads and metadata-only noise.
+    fn from_event(kind: &EventKind) -> Option<Self> {
+        match kind {
+            EventKind::Create(_) => Some(ChangeKind::Created),
+            EventKind::Remove(_) => Some(ChangeKind::Removed),
+            EventKind::Modify(ModifyKind::Name(RenameMode::From)) => None,
+            EventKind::Modify(ModifyKind::Name(_)) => Some(ChangeKind::Renamed),
+            EventKind::Modify(ModifyKind::Metadata(_)) => None,
+            EventKind::Modify(_) | EventKind::Any => Some(ChangeKind::Modified),
+            EventKind::Access(_) | EventKind::Other => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ChangeKind::Modified => "modified",
+            ChangeKind::Created => "created",
+            ChangeKind::Removed => "removed",
+            ChangeKind::Renamed => "renamed",
+        }
+    }
+}
+
+/// Switches the REPL can flip while the watcher runs, plus the change log
+/// it records into.
+pub struct WatchControl {
+    enabled: AtomicBool,
+    verbose: AtomicBool,
+    pub log: ChangeLog,
+}
+
+impl WatchControl {
+    pub fn new(verbose: bool, log: ChangeLog) -> Self {
+        Self {
+            enabled: AtomicBool::new(true),
+            verbose: AtomicBool::new(verbose),
+            log,
+        }
+    }
+
+    /// While off, changes are neither recorded nor count as activity.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Whether a summary line is printed for every batch of changes.
+    pub fn is_verbose(&self) -> bool {
+        self.verbose.load(Ordering::Relaxed)
+    }
+
+    pub fn set_verbose(&self, verbose: bool) {
+        self.verbose.store(verbose, Ordering::Relaxed);
+    }
+}
+
+/// Decides which paths under the watched folder are worth reporting: never
+/// `.git/`, nothing the project's `.gitignore` excludes and nothing matching
+/// the configured globs.
+pub struct WatchFilter {
+    root: PathBuf,
+    gitignore: Gitignore,
+    globs: GlobSet,
+}
+
+impl WatchFilter {
+    pub fn new(root: &Path, globs: &[String]) -> Result<Self, String> {
+        let mut gitignore = GitignoreBuilder::new(root);
+        for file in [root.join(".gitignore"), root.join(".git").join("info").join("exclude")] {
+            if file.exists() {
+                if let Some(err) = gitignore.add(&file) {
+                    println!("{}", format!("⚠️  Ignoring bad patterns in {}: {}", file.display(), err).bright_yellow());
+                }
+            }
+        }
+        let gitignore = gitignore.build().map_err(|e| e.to_string())?;
+
+        let mut set = GlobSetBuilder::new();
+        for glob in globs {
+            set.add(Glob::new(glob).map_err(|e| format!("invalid watcher.ignore glob '{}': {}", glob, e))?);
+        }
+
+        Ok(Self {
+            root: root.to_path_buf(),
+            gitignore,
+            globs: set.build().map_err(|e| e.to_string())?,
+        })
+    }
+
+    /// `path` relative to the watched folder, or `None` if it's ignored.
+    pub fn relative(&self, path: &Path) -> Option<PathBuf> {
+        let path: PathBuf = path.components().filter(|c| *c != Component::CurDir).collect();
+        let relative = path.strip_prefix(&self.root).ok()?.to_path_buf();
+        if relative.as_os_str().is_empty() || relative.starts_with(".git") {
+            return None;
+        }
+        if self.gitignore.matched_path_or_any_parents(&relative, false).is_ignore() {
+            return None;
+        }
+        let name_matches = relative.file_name().is_some_and(|name| self.globs.is_match(name));
+        if name_matches || self.globs.is_match(&relative) {
+            return None;
+        }
+        Some(relative)
+    }
+}
+
+/// Changes seen during one debounce window, one entry per path.
+#[derive(Default)]
+pub struct ChangeBatch {
+    changes: BTreeMap<PathBuf, ChangeKind>,
+}
+
+impl ChangeBatch {
+    pub fn add(&mut self, path: PathBuf, kind: ChangeKind) {
+        let merged = match (self.changes.get(&path), kind) {
+            (Some(ChangeKind::Created), ChangeKind::Modified) => ChangeKind::Created,
+            // Short-lived files (swap files, temp outputs) leave no trace.
+            (Some(ChangeKind::Created), ChangeKind::Removed) => {
+                self.changes.remove(&path);
+                return;
+            }
+            // Editors that save by replacing the file.
+            (Some(ChangeKind::Removed), ChangeKind::Created) => ChangeKind::Modified,
+            _ => kind,
+        };
+        self.changes.insert(path, merged);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&PathBuf, &ChangeKind)> {
+        self.changes.iter()
+    }
+
+    /// One line for the whole batch, e.g. "3 files modified in src/, 1 created".
+    pub fn summary(&self) -> String {
+        if let [(path, kind)] = self.changes.iter().collect::<Vec<_>>()[..] {
+            return format!("{} {}", path.display(), kind.label());
+        }
+
+        let mut by_kind: BTreeMap<ChangeKind, Vec<&PathBuf>> = BTreeMap::new();
+        for (path, kind) in &self.changes {
+            by_kind.entry(*kind).or_default().push(path);
+        }
+        let parts: Vec<String> = by_kind
+            .iter()
+            .enumerate()
+            .map(|(i, (kind, paths))| {
+                let noun = match (i, paths.len()) {
+                    (0, 1) => " file",
+                    (0, _) => " files",
+                    _ => "",
+                };
+                let parent = paths[0].parent().filter(|p| !p.as_os_str().is_empty());
+                let place = match parent {
+                    Some(dir) if paths.iter().all(|p| p.parent() == Some(dir)) => format!(" in {}/", dir.display()),
+                    _ => String::new(),
+                };
+                format!("{}{} {}{}", paths.len(), noun, kind.label(), place)
+            })
+            .collect();
+        parts.join(", ")
+    }
+}
+
+/// Watch `folder`, recording changes into `control.log` and counting them
+/// as activity. Summary lines are only printed in verbose mode.
+pub fn watch_folder(folder: &str, activity: Arc<Activity>, options: WatchOptions, control: Arc<WatchControl>) {
+    let root = fs::canonicalize(folder).unwrap_or_else(|_| PathBuf::from(folder));
+    let setup = WatchFilter::new(&root, &options.ignore)
+        .and_then(|filter| Ok((filter, ActionRunner::new(&options.actions, &root, &options.confirm_commands)?)));
+    let (filter, actions) = match setup {
+        Ok(setup) => setup,
+        Err(err) => {
+            println!("{}", format!("❌ File watcher disabled: {}", err).bright_red());
+            return;
+        }
+    };
 
-pub fn watch_folder(folder: &str) {
     let (tx, rx) = channel();
 
     let config = Config::default()
-        .with_poll_interval(Duration::from_millis(200));
+        .with_poll_interval(options.poll_interval);
 
     let mut watcher: RecommendedWatcher =
         Watcher::new(tx, config).unwrap();
 
     watcher
-        .watch(Path::new(folder), RecursiveMode::Recursive)
+        .watch(&root, RecursiveMode::Recursive)
         .unwrap();
 
-    for event in rx {
-        if let Ok(ev) = event {
-            if let Some(path) = ev.paths.get(0) {
-                println!("{} {}", "📂 File changed:".yellow(), path.display());
+    let mut batch = ChangeBatch::default();
+    let mut batch_started = Instant::now();
+    loop {
+        let event = if batch.is_empty() {
+            rx.recv().map_err(|_| RecvTimeoutError::Disconnected)
+        } else {
+            rx.recv_timeout(options.debounce)
+        };
+        let quiet = matches!(event, Err(RecvTimeoutError::Timeout));
+        match event {
+            Ok(Ok(_)) if !control.is_enabled() => {}
+            Ok(Ok(event)) => {
+                let kind = ChangeKind::from_event(&event.kind);
+                // For a rename with both ends the destination is the interesting path.
+                let paths = match event.kind {
+                    EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => event.paths.last().into_iter().collect(),
+                    _ => event.paths.iter().collect::<Vec<_>>(),
+                };
+                for path in paths {
+                    let (Some(kind), Some(relative)) = (kind, filter.relative(path)) else {
+                        continue;
+                    };
+                    activity.touch();
+                    if path.is_dir() {
+                        continue;
+                    }
+                    if batch.is_empty() {
+                        batch_started = Instant::now();
+                    }
+                    activity.file_changed(&relative);
+                    control.log.record(&relative, kind);
+                    batch.add(relative, kind);
+                }
             }
+            Ok(Err(_)) => {}
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+
+        if !batch.is_empty() && (quiet || batch_started.elapsed() >= options.debounce * MAX_BATCH_WINDOWS) {
+            if control.is_verbose() {
+                println!("{} {}", "📂".yellow(), batch.summary().yellow());
+            }
+            actions.trigger(&batch.iter().map(|(path, _)| path).collect::<Vec<_>>());
+            batch = ChangeBatch::default();
         }
     }
 }
-